@@ -1,14 +1,14 @@
-use crate::{style, utils};
+use crate::style;
 use bevy::prelude::*;
 
-#[derive(Resource)]
+#[derive(Resource, Default)]
 pub struct GameAssets {
     pub begin_wave_audio: Handle<AudioSource>,
     pub game_over_audio: Handle<AudioSource>,
     pub collision_audio: Handle<AudioSource>,
     pub laser_audio: Handle<AudioSource>,
-    pub thrust_audio: Handle<AudioSource>,
     pub capture_audio: Handle<AudioSource>,
+    pub engine_audio: Handle<AudioSource>,
     pub player_texture: Handle<Image>,
     pub enemy_texture: Handle<Image>,
     pub mutant_texture: Handle<Image>,
@@ -32,8 +32,8 @@ pub fn load(
         game_over_audio: asset_server.load(style::GAME_OVER_SOUND),
         collision_audio: asset_server.load(style::COLLISION_SOUND),
         laser_audio: asset_server.load(style::LASER_SOUND),
-        thrust_audio: asset_server.load(style::THRUST_SOUND),
         capture_audio: asset_server.load(style::CAPTURE_SOUND),
+        engine_audio: asset_server.load(style::THRUST_SOUND),
         player_texture: asset_server.load(style::PLAYER_TEXTURE),
         enemy_texture: asset_server.load(style::ENEMY_TEXTURE),
         mutant_texture: asset_server.load(style::MUTANT_TEXTURE),
@@ -48,13 +48,6 @@ pub fn load(
     })
 }
 
-pub fn audio(source: Handle<AudioSource>, volume: f32) -> AudioBundle {
-    AudioBundle {
-        source,
-        settings: PlaybackSettings::DESPAWN.with_volume(utils::bevy::volume(volume)),
-    }
-}
-
 pub trait MyTransform {
     fn transform(angle: f32) -> Transform;
 }
@@ -0,0 +1,253 @@
+use crate::{
+    assets::GameAssets,
+    autopilot::Autopilot,
+    enemy,
+    high_scores::HighScoreText,
+    menu::PausedMenu,
+    new_game::NewGame,
+    player::{input::Devices, rebind},
+    rng::GameRng,
+    score::Score,
+    settings::SettingsMenu,
+    style, utils,
+};
+use bevy::{input::mouse::MouseMotion, prelude::*};
+use utils::bevy::state::GameState;
+
+/// A page of the attract cycle shown while the title screen is left alone.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Page {
+    Title,
+    Roster,
+    HighScores,
+    Demo,
+}
+
+impl Page {
+    fn next(self) -> Self {
+        match self {
+            Page::Title => Page::Roster,
+            Page::Roster => Page::HighScores,
+            Page::HighScores => Page::Demo,
+            Page::Demo => Page::Title,
+        }
+    }
+
+    /// Seconds before moving on to the next page.
+    fn time(self) -> f32 {
+        match self {
+            Page::Title => 10.0,
+            Page::Roster | Page::HighScores => 6.0,
+            Page::Demo => 30.0,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct Cycle {
+    page: Page,
+    elapsed: f32,
+}
+
+/// Present while the demo plays, keeps the score of the last real run to
+/// put back afterwards.
+#[derive(Resource)]
+pub struct Demo {
+    score: u32,
+}
+
+#[derive(Component)]
+struct Roster;
+
+/// Whether the game is played for real rather than by the demo.
+pub fn live(demo: Option<Res<Demo>>) -> bool {
+    demo.is_none()
+}
+
+pub struct Plug;
+
+impl Plugin for Plug {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Cycle {
+            page: Page::Title,
+            elapsed: 0.0,
+        })
+        .add_systems(PostStartup, spawn_roster)
+        .add_systems(OnEnter(GameState::Title), restart)
+        .add_systems(
+            PreUpdate,
+            advance
+                .after(bevy::input::InputSystem)
+                .run_if(in_state(GameState::Title).or_else(resource_exists::<Demo>())),
+        )
+        .add_systems(Update, show_page);
+    }
+}
+
+fn restart(mut cycle: ResMut<Cycle>) {
+    cycle.page = Page::Title;
+    cycle.elapsed = 0.0;
+}
+
+/// Moves through the pages, back to the title on any input or when the
+/// demo pilot dies.
+#[allow(clippy::too_many_arguments)]
+fn advance(
+    mut cycle: ResMut<Cycle>,
+    time: Res<Time<Real>>,
+    devices: Devices,
+    mut motion: EventReader<MouseMotion>,
+    menu_query: Query<&Style, With<PausedMenu>>,
+    rebinding: Res<rebind::Rebinding>,
+    state: Res<State<GameState>>,
+    demo: Option<Res<Demo>>,
+    mut commands: Commands,
+    mut score: ResMut<Score>,
+    rng: Res<GameRng>,
+    mut new_game: EventWriter<NewGame>,
+) {
+    let Ok(menu) = menu_query.get_single() else {
+        return;
+    };
+    let input = devices.any_just_pressed().is_some() || motion.read().count() > 0;
+    let busy =
+        cycle.page == Page::Title && (menu.display == Display::None || rebinding.action.is_some());
+    cycle.elapsed += time.delta_seconds();
+    if input || busy {
+        cycle.elapsed = 0.0;
+    }
+    let died = demo.is_some() && *state.get() == GameState::PlayerDeath;
+    let next = match (input, died) {
+        (true, _) | (_, true) => Page::Title,
+        _ if cycle.elapsed >= cycle.page.time() => cycle.page.next(),
+        _ => return,
+    };
+    if next == cycle.page {
+        return;
+    }
+    cycle.page = next;
+    cycle.elapsed = 0.0;
+    match next {
+        Page::Demo => {
+            commands.insert_resource(Demo { score: score.value });
+            commands.insert_resource(Autopilot);
+            new_game.send(NewGame {
+                seed: rng.next_seed(),
+            });
+        }
+        Page::Title => {
+            if let Some(demo) = demo {
+                score.value = demo.score;
+                commands.remove_resource::<Demo>();
+                commands.remove_resource::<Autopilot>();
+                commands.insert_resource(NextState(Some(GameState::Title)));
+            }
+        }
+        _ => {}
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn show_page(
+    cycle: Res<Cycle>,
+    mut shown: Local<Option<Page>>,
+    state: Res<State<GameState>>,
+    mut menu_query: Query<&mut Style, With<PausedMenu>>,
+    mut settings_query: Query<&mut Style, (With<SettingsMenu>, Without<PausedMenu>)>,
+    mut high_score_query: Query<
+        &mut Style,
+        (
+            With<HighScoreText>,
+            Without<PausedMenu>,
+            Without<SettingsMenu>,
+        ),
+    >,
+    mut roster_query: Query<
+        &mut Style,
+        (
+            With<Roster>,
+            Without<HighScoreText>,
+            Without<PausedMenu>,
+            Without<SettingsMenu>,
+        ),
+    >,
+) {
+    if *shown == Some(cycle.page) || *state.get() != GameState::Title {
+        return;
+    }
+    let (Ok(mut menu), Ok(mut settings), Ok(mut high_scores), Ok(mut roster)) = (
+        menu_query.get_single_mut(),
+        settings_query.get_single_mut(),
+        high_score_query.get_single_mut(),
+        roster_query.get_single_mut(),
+    ) else {
+        return;
+    };
+    *shown = Some(cycle.page);
+    let display = |on| if on { Display::Flex } else { Display::None };
+    if cycle.page != Page::Title {
+        menu.display = Display::None;
+        settings.display = Display::None;
+    } else {
+        menu.display = Display::Flex;
+    }
+    high_scores.display = display(matches!(cycle.page, Page::Title | Page::HighScores));
+    roster.display = display(cycle.page == Page::Roster);
+}
+
+fn spawn_roster(mut commands: Commands, asset_server: Res<AssetServer>, assets: Res<GameAssets>) {
+    let text_style = |font_size| TextStyle {
+        font: asset_server.load(style::FONT),
+        font_size,
+        color: style::SCORE_COLOR,
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    display: Display::None,
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(24.0),
+                    ..default()
+                },
+                ..default()
+            },
+            Roster,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("ENEMIES", text_style(40.0)));
+            for (variant, texture) in enemy::roster(&assets) {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(32.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(ImageBundle {
+                            style: Style {
+                                width: Val::Px(64.0),
+                                height: Val::Px(64.0),
+                                ..default()
+                            },
+                            image: UiImage::new(texture),
+                            ..default()
+                        });
+                        let label = format!(
+                            "{:<8}{:>4} PTS",
+                            variant.name.to_uppercase(),
+                            variant.points
+                        );
+                        parent.spawn(TextBundle::from_section(label, text_style(32.0)));
+                    });
+            }
+        });
+}
@@ -0,0 +1,199 @@
+use crate::{assets::GameAssets, settings::Settings, utils};
+use bevy::{audio::PlaybackMode, prelude::*};
+use utils::bevy::window;
+
+/// Distance between the ears of the listener. Sounds are placed between
+/// them, so panning never brings in distance attenuation.
+const EAR_GAP: f32 = 1.0;
+/// How fast sounds fade per screen width they are off-screen.
+const OFF_SCREEN_FALLOFF: f32 = 2.0;
+
+/// Mixer channel a sound is played on, each with its own volume setting.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Bus {
+    Sfx,
+    Voice,
+    Music,
+    Engine,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Sound {
+    Laser,
+    Collision,
+    Capture,
+    BeginWave,
+    GameOver,
+    /// A short burst of the engine loop pitched up, for lack of a one-shot
+    /// asset of its own. Replace the source once there is one.
+    Boost,
+    /// The engine loop pitched down, likewise a stand-in for a one-shot.
+    Brake,
+}
+
+impl Sound {
+    fn source(&self, assets: &GameAssets) -> Handle<AudioSource> {
+        match self {
+            Sound::Laser => assets.laser_audio.clone(),
+            Sound::Collision => assets.collision_audio.clone(),
+            Sound::Capture => assets.capture_audio.clone(),
+            Sound::Boost | Sound::Brake => assets.engine_audio.clone(),
+            Sound::BeginWave => assets.begin_wave_audio.clone(),
+            Sound::GameOver => assets.game_over_audio.clone(),
+        }
+    }
+
+    pub fn bus(&self) -> Bus {
+        match self {
+            Sound::Laser | Sound::Collision | Sound::Capture => Bus::Sfx,
+            Sound::BeginWave | Sound::GameOver => Bus::Voice,
+            Sound::Boost | Sound::Brake => Bus::Engine,
+        }
+    }
+
+    fn loudness(&self) -> f32 {
+        match self {
+            Sound::Collision => 1.5,
+            _ => 1.0,
+        }
+    }
+
+    /// Copies allowed at once, more are dropped.
+    fn max_copies(&self) -> usize {
+        match self {
+            Sound::Laser => 4,
+            Sound::Collision => 3,
+            Sound::Capture => 2,
+            Sound::BeginWave | Sound::GameOver | Sound::Boost | Sound::Brake => 1,
+        }
+    }
+
+    /// Playback speed, the engine pitched up and down makes the boost and
+    /// brake.
+    fn speed(&self) -> f32 {
+        match self {
+            Sound::Boost => 1.6,
+            Sound::Brake => 0.7,
+            _ => 1.0,
+        }
+    }
+
+    /// Seconds it's cut off after, for bursts of longer sounds.
+    fn length(&self) -> Option<f32> {
+        match self {
+            Sound::Boost => Some(0.4),
+            Sound::Brake => Some(0.5),
+            _ => None,
+        }
+    }
+}
+
+/// Asks for `sound` to be played, panned to `position` if it has one.
+#[derive(Event)]
+pub struct Play {
+    pub sound: Sound,
+    pub position: Option<Vec3>,
+}
+
+impl Play {
+    pub fn at(sound: Sound, position: Vec3) -> Self {
+        Self {
+            sound,
+            position: Some(position),
+        }
+    }
+
+    pub fn centered(sound: Sound) -> Self {
+        Self {
+            sound,
+            position: None,
+        }
+    }
+}
+
+#[derive(Component)]
+struct Playing(Sound);
+
+/// Real time a sound is cut off at.
+#[derive(Component)]
+struct Cut(f32);
+
+pub struct Plug;
+
+impl Plugin for Plug {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Play>();
+        if !utils::bevy::headless(app) {
+            app.add_systems(Startup, spawn_listener)
+                .add_systems(PostUpdate, (play, cut));
+        }
+    }
+}
+
+/// Pan from -1 (left) to 1 (right) and gain for a sound at `x`.
+pub fn placement(x: f32, camera_x: f32, window_width: f32) -> (f32, f32) {
+    let offset = (x - camera_x) / (window_width * 0.5);
+    let off_screen = (offset.abs() - 1.0).max(0.0) * 0.5;
+    (
+        offset.clamp(-1.0, 1.0),
+        1.0 / (1.0 + off_screen * OFF_SCREEN_FALLOFF),
+    )
+}
+
+fn spawn_listener(mut commands: Commands) {
+    commands.spawn((SpatialListener::new(EAR_GAP), TransformBundle::default()));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn play(
+    mut events: EventReader<Play>,
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    settings: Res<Settings>,
+    playing_query: Query<&Playing>,
+    camera_query: Query<&Transform, With<Camera>>,
+    window_size: Res<window::Size>,
+    time: Res<Time<Real>>,
+) {
+    let mut copies: Vec<Sound> = playing_query.iter().map(|playing| playing.0).collect();
+    let camera_x = camera_query.single().translation.x;
+    for event in events.read() {
+        let sound = event.sound;
+        if copies.iter().filter(|other| **other == sound).count() >= sound.max_copies() {
+            continue;
+        }
+        copies.push(sound);
+        let (pan, gain) = match event.position {
+            Some(position) => placement(position.x, camera_x, window_size.0.x),
+            None => (0.0, 1.0),
+        };
+        let level = gain * sound.loudness() * settings.bus(sound.bus());
+        let position = Vec3::X * pan * EAR_GAP * 0.5;
+        let mut entity = commands.spawn((
+            AudioBundle {
+                source: sound.source(&assets),
+                settings: PlaybackSettings {
+                    mode: PlaybackMode::Despawn,
+                    volume: utils::bevy::volume(level),
+                    speed: sound.speed(),
+                    spatial: true,
+                    ..default()
+                },
+            },
+            Transform::from_translation(position),
+            GlobalTransform::from_translation(position),
+            Playing(sound),
+        ));
+        if let Some(length) = sound.length() {
+            entity.insert(Cut(time.elapsed_seconds() + length));
+        }
+    }
+}
+
+fn cut(mut commands: Commands, query: Query<(Entity, &Cut)>, time: Res<Time<Real>>) {
+    for (entity, cut) in query.iter() {
+        if cut.0 <= time.elapsed_seconds() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use crate::{
+    enemy::Enemy,
+    map,
+    person::{CharacterState, Person},
+    player::{input, Player},
+    projectile::orb::Orb,
+    style, utils,
+};
+use bevy::prelude::*;
+use input::Controls;
+use utils::{
+    bevy::{projectile::Projectile, window},
+    Side,
+};
+
+/// How far from its target the ship tries to stay while shooting.
+const STANDOFF: f32 = 250.0;
+/// Height difference at which a shot still hits.
+const AIM_TOLERANCE: f32 = 12.0;
+/// Height difference below which the ship stops climbing or diving.
+const HOVER_TOLERANCE: f32 = 6.0;
+/// Orbs closer in time than this are dodged.
+const DODGE_TIME: f32 = 0.75;
+/// Orbs passing closer than this are dodged.
+const DODGE_MARGIN: f32 = 48.0;
+/// Falling humanoids further than this are left alone.
+const RESCUE_RANGE: f32 = 1500.0;
+
+/// While present, the pilot flies the ship instead of the player.
+#[derive(Resource)]
+pub struct Autopilot;
+
+/// What the pilot knows about the world, positions are the simulated ones.
+#[derive(Default)]
+pub struct View {
+    pub player: Vec3,
+    pub facing: Option<Side>,
+    pub enemies: Vec<Vec3>,
+    /// Position and velocity of every orb.
+    pub orbs: Vec<(Vec3, Vec3)>,
+    pub falling: Vec<Vec3>,
+    pub carrying: bool,
+    /// Highest the ship can go.
+    pub ceiling: f32,
+}
+
+/// Horizontal distance from `from` to `to`, the short way around the map.
+fn wrap(from: f32, to: f32) -> f32 {
+    (to - from + map::SIZE * 0.5).rem_euclid(map::SIZE) - map::SIZE * 0.5
+}
+
+impl View {
+    fn nearest<'a>(&self, points: impl Iterator<Item = &'a Vec3>) -> Option<Vec3> {
+        let distance = |p: &Vec3| Vec2::new(wrap(self.player.x, p.x), p.y - self.player.y);
+        points
+            .min_by(|a, b| distance(a).length().total_cmp(&distance(b).length()))
+            .copied()
+    }
+
+    /// Which way to move out of the way of the most pressing orb, if any.
+    fn dodge(&self) -> Option<f32> {
+        let mut threat: Option<(f32, Vec2)> = None;
+        for (position, velocity) in &self.orbs {
+            let offset = Vec2::new(wrap(position.x, self.player.x), self.player.y - position.y);
+            let velocity = velocity.truncate();
+            let speed = velocity.length_squared();
+            if speed == 0.0 {
+                continue;
+            }
+            let t = offset.dot(velocity) / speed;
+            let miss = offset - velocity * t;
+            let sooner = threat.is_none_or(|(other, _)| t < other);
+            if t > 0.0 && t < DODGE_TIME && miss.length() < DODGE_MARGIN && sooner {
+                threat = Some((t, miss));
+            }
+        }
+        let (_, miss) = threat?;
+        let up = match miss.y {
+            y if y != 0.0 => y > 0.0,
+            _ => miss.x.abs() < 1.0,
+        };
+        let up = match up {
+            true => self.player.y < self.ceiling - DODGE_MARGIN,
+            false => self.player.y < style::BORDER_CONFINEMENT_OFFSET + DODGE_MARGIN,
+        };
+        Some(if up { 1.0 } else { -1.0 })
+    }
+}
+
+/// Flies towards falling humanoids to catch them, otherwise hunts the
+/// nearest enemy, always getting out of the way of orbs first.
+pub fn pilot(view: &View) -> Controls {
+    let mut controls = Controls::default();
+    let toward = |x: f32| match wrap(view.player.x, x) >= 0.0 {
+        true => Side::Right,
+        false => Side::Left,
+    };
+    let thrust = |controls: &mut Controls, side| match side {
+        Side::Left => controls.move_left = true,
+        Side::Right => controls.move_right = true,
+    };
+    let falling = view
+        .nearest(view.falling.iter())
+        .filter(|p| !view.carrying && wrap(view.player.x, p.x).abs() < RESCUE_RANGE);
+    let target_y = if let Some(person) = falling {
+        controls.rescue = true;
+        if wrap(view.player.x, person.x).abs() > style::PLAYER_BOUND.x * 0.25 {
+            thrust(&mut controls, toward(person.x));
+        }
+        Some(person.y)
+    } else if let Some(enemy) = view.nearest(view.enemies.iter()) {
+        let side = toward(enemy.x);
+        if wrap(view.player.x, enemy.x).abs() > STANDOFF || view.facing != Some(side) {
+            thrust(&mut controls, side);
+        }
+        let dy = enemy.y - view.player.y;
+        controls.shoot = view.facing == Some(side) && dy.abs() < AIM_TOLERANCE;
+        Some(enemy.y)
+    } else {
+        None
+    };
+    let vertical = view.dodge().or_else(|| {
+        let dy = target_y? - view.player.y;
+        (dy.abs() > HOVER_TOLERANCE).then_some(dy.signum())
+    });
+    match vertical {
+        Some(v) if v > 0.0 => controls.move_up = true,
+        Some(_) => controls.move_down = true,
+        None => {}
+    }
+    controls
+}
+
+pub struct Plug;
+
+impl Plugin for Plug {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            drive
+                .after(input::input)
+                .run_if(resource_exists::<Autopilot>()),
+        );
+    }
+}
+
+fn drive(
+    mut controls: ResMut<Controls>,
+    player_query: Query<(Entity, &Transform, &Player)>,
+    enemy_query: Query<&Transform, With<Enemy>>,
+    orb_query: Query<(&Transform, &Projectile), With<Orb>>,
+    person_query: Query<(&Transform, &CharacterState), With<Person>>,
+    window_size: Res<window::Size>,
+) {
+    let Ok((player_entity, transform, player)) = player_query.get_single() else {
+        *controls = Controls::default();
+        return;
+    };
+    let view = View {
+        player: transform.translation,
+        facing: Some(player.facing),
+        enemies: enemy_query.iter().map(|t| t.translation).collect(),
+        orbs: orb_query
+            .iter()
+            .map(|(t, projectile)| (t.translation, projectile.velocity))
+            .collect(),
+        falling: person_query
+            .iter()
+            .filter(|(_, state)| matches!(state, CharacterState::Falling))
+            .map(|(t, _)| t.translation)
+            .collect(),
+        carrying: person_query.iter().any(|(_, state)| {
+            matches!(state, CharacterState::CapturedBy(captor, _) if *captor == player_entity)
+        }),
+        ceiling: window_size.0.y * (1.0 - style::MINIMAP_SIZE.y) - style::BORDER_CONFINEMENT_OFFSET,
+    };
+    *controls = pilot(&view);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        harness::{Harness, FRAMES_PER_SECOND, WINDOW_SIZE},
+        score::Score,
+    };
+    use utils::bevy::{game_time::GameTime, state::GameState};
+
+    fn base() -> View {
+        View {
+            player: Vec3::new(0.0, 300.0, 0.0),
+            facing: Some(Side::Right),
+            ceiling: 500.0,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn hunts_nearest_enemy_around_the_map() {
+        let view = View {
+            enemies: vec![
+                Vec3::new(map::SIZE - 400.0, 300.0, 0.0),
+                Vec3::new(900.0, 300.0, 0.0),
+            ],
+            ..base()
+        };
+        let controls = pilot(&view);
+        assert!(controls.move_left && !controls.shoot);
+
+        let view = View {
+            enemies: vec![Vec3::new(200.0, 310.0, 0.0)],
+            ..base()
+        };
+        let controls = pilot(&view);
+        assert!(controls.shoot && controls.move_up && !controls.move_right);
+    }
+
+    #[test]
+    fn dodges_orbs_on_course() {
+        let orb = (Vec3::new(100.0, 290.0, 0.0), Vec3::new(-300.0, 0.0, 0.0));
+        let view = View {
+            orbs: vec![orb],
+            ..base()
+        };
+        assert!(pilot(&view).move_up);
+
+        let passing = (Vec3::new(100.0, 100.0, 0.0), Vec3::new(-300.0, 0.0, 0.0));
+        let view = View {
+            orbs: vec![passing],
+            ..base()
+        };
+        assert!(!pilot(&view).move_up && !pilot(&view).move_down);
+    }
+
+    #[test]
+    fn catches_falling_humanoids_first() {
+        let view = View {
+            enemies: vec![Vec3::new(200.0, 300.0, 0.0)],
+            falling: vec![Vec3::new(-600.0, 200.0, 0.0)],
+            ..base()
+        };
+        let controls = pilot(&view);
+        assert!(controls.rescue && controls.move_left && controls.move_down);
+    }
+
+    /// Runs the pilot through many runs and reports how they went. Catches
+    /// panics and an enemy count that drifts from the enemies there are.
+    #[test]
+    fn soak() {
+        const RUNS: u64 = 8;
+        const MAX_SECONDS: f32 = 60.0;
+        let mut harness = Harness::new(WINDOW_SIZE);
+        harness.app.insert_resource(Autopilot);
+        let mut results = vec![];
+        for seed in 0..RUNS {
+            harness.start(seed);
+            let start = harness.app.world.resource::<GameTime>().elapsed_seconds();
+            let frames = (MAX_SECONDS * FRAMES_PER_SECOND as f32) as usize;
+            for _ in 0..frames {
+                harness.app.update();
+                if harness.state() == GameState::Playing {
+                    let count = harness
+                        .app
+                        .world
+                        .resource::<crate::enemy::EnemiesCount>()
+                        .count;
+                    assert_eq!(count as usize, harness.count::<Enemy>(), "seed {}", seed);
+                }
+                if !harness.state().simulating() && harness.state() != GameState::WaveIntro {
+                    break;
+                }
+            }
+            let survived = harness.app.world.resource::<GameTime>().elapsed_seconds() - start;
+            let score = harness.app.world.resource::<Score>().value;
+            results.push((survived, score));
+        }
+        let report = |name: &str, mut values: Vec<f32>| {
+            values.sort_by(f32::total_cmp);
+            println!(
+                "{}: min {:.1} median {:.1} max {:.1}",
+                name,
+                values[0],
+                values[values.len() / 2],
+                values[values.len() - 1]
+            );
+        };
+        report("survived seconds", results.iter().map(|r| r.0).collect());
+        report("score", results.iter().map(|r| r.1 as f32).collect());
+    }
+}
@@ -1,8 +1,14 @@
 use bevy::{core_pipeline::bloom::BloomSettings, prelude::*};
 
 use crate::{
+    explosion,
     player::{Player, HORIZONTAL_SPEED},
-    utils::{self, bevy::window},
+    rng::CosmeticRng,
+    settings::Settings,
+    utils::{
+        self,
+        bevy::{fixed::Interpolated, window},
+    },
 };
 
 pub fn spawn(mut commands: Commands) {
@@ -15,6 +21,7 @@ pub fn spawn(mut commands: Commands) {
             ..default()
         },
         BloomSettings::default(),
+        Interpolated::default(),
     ));
 }
 
@@ -49,3 +56,37 @@ pub fn follow_player(
         camera.translation.x = x;
     }
 }
+
+/// Furthest the camera is thrown by a shake at full trauma and intensity.
+const MAX_SHAKE: f32 = 12.0;
+/// Trauma lost per second.
+const SHAKE_DECAY: f32 = 2.0;
+
+/// How shaken the camera is, from 0 to 1. Shaking goes with its square so
+/// small hits barely move it.
+#[derive(Resource, Default)]
+pub struct Shake {
+    trauma: f32,
+}
+
+/// Offsets the rendered camera after interpolation, the simulated position
+/// is put back before the next tick.
+pub fn shake(
+    mut shake: ResMut<Shake>,
+    mut explosions: EventReader<explosion::At>,
+    mut camera_query: Query<&mut Transform, With<Camera>>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    mut rng: ResMut<CosmeticRng>,
+) {
+    for _ in explosions.read() {
+        shake.trauma = (shake.trauma + 0.5).min(1.0);
+    }
+    if shake.trauma <= 0.0 {
+        return;
+    }
+    let amount = shake.trauma.powi(2) * settings.screen_shake * MAX_SHAKE;
+    let direction = utils::bevy::clock(rng.f32());
+    camera_query.single_mut().translation += (direction * amount).extend(0.0);
+    shake.trauma = (shake.trauma - SHAKE_DECAY * time.delta_seconds()).max(0.0);
+}
@@ -0,0 +1,64 @@
+use crate::high_scores::HighScores;
+
+#[derive(Default)]
+pub struct Args {
+    pub export_high_scores: Option<String>,
+    pub import_high_scores: Option<String>,
+    /// Shows the enemy shot patterns instead of the game.
+    pub patterns: bool,
+    pub record: Option<String>,
+    pub replay: Option<String>,
+    pub seed: Option<u64>,
+    pub tick_rate: Option<f64>,
+}
+
+impl Args {
+    pub fn parse() -> Result<Self, String> {
+        let mut args = Args::default();
+        let mut it = std::env::args().skip(1);
+        while let Some(flag) = it.next() {
+            let mut value = || it.next().ok_or(format!("missing value for {}", flag));
+            match flag.as_str() {
+                "--export-high-scores" => args.export_high_scores = Some(value()?),
+                "--import-high-scores" => args.import_high_scores = Some(value()?),
+                "--patterns" => args.patterns = true,
+                "--record" => args.record = Some(value()?),
+                "--replay" => args.replay = Some(value()?),
+                "--seed" => {
+                    let seed = value()?;
+                    let seed = seed.parse().map_err(|_| format!("invalid seed {}", seed))?;
+                    args.seed = Some(seed);
+                }
+                "--tick-rate" => {
+                    let hz = value()?;
+                    let hz = hz
+                        .parse()
+                        .map_err(|_| format!("invalid tick rate {}", hz))?;
+                    args.tick_rate = Some(hz);
+                }
+                _ => return Err(format!("unknown flag {}", flag)),
+            }
+        }
+        Ok(args)
+    }
+
+    /// Runs the commands that need no window, returns whether there were any.
+    pub fn run_headless(&self) -> Result<bool, String> {
+        if self.import_high_scores.is_none() && self.export_high_scores.is_none() {
+            return Ok(false);
+        }
+        let mut high_scores =
+            HighScores::load().map_err(|error| format!("high scores: {}", error))?;
+        if let Some(path) = &self.import_high_scores {
+            high_scores
+                .import(path)
+                .map_err(|error| format!("import {}: {}", path, error))?;
+        }
+        if let Some(path) = &self.export_high_scores {
+            high_scores
+                .export(path)
+                .map_err(|error| format!("export {}: {}", path, error))?;
+        }
+        Ok(true)
+    }
+}
@@ -0,0 +1,230 @@
+use crate::{
+    attract,
+    enemy::{self, EnemiesCount},
+    game_over::GameOver,
+    new_game::NewGame,
+    settings::Settings,
+    utils,
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use utils::bevy::state::GameState;
+
+/// How much each lost run in a row eases off in adaptive mode.
+const ADAPTIVE_EASE: f32 = 0.85;
+/// Lost runs in a row past which adaptive mode eases off no further.
+const MAX_STREAK: u32 = 3;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Debug, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Insane,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Insane,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Insane => "insane",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name() == name)
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Insane,
+            Difficulty::Insane => Difficulty::Easy,
+        }
+    }
+
+    fn scale(&self) -> Scale {
+        match self {
+            Difficulty::Easy => Scale {
+                shot_delay: 1.5,
+                orb_speed: 0.75,
+                aim: 0.6,
+                enemy_speed: 0.8,
+                abduction_speed: 0.6,
+                enemy_count: 0.6,
+            },
+            Difficulty::Normal => Scale::default(),
+            Difficulty::Hard => Scale {
+                shot_delay: 0.75,
+                orb_speed: 1.25,
+                aim: 0.15,
+                enemy_speed: 1.25,
+                abduction_speed: 1.25,
+                enemy_count: 1.3,
+            },
+            Difficulty::Insane => Scale {
+                shot_delay: 0.5,
+                orb_speed: 1.5,
+                aim: 0.05,
+                enemy_speed: 1.5,
+                abduction_speed: 1.75,
+                enemy_count: 1.6,
+            },
+        }
+    }
+}
+
+/// How enemies behave compared to normal, all factors but `aim`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Scale {
+    pub shot_delay: f32,
+    pub orb_speed: f32,
+    /// Exponent of how well lined up a shot must be to be fired, lower
+    /// fires more of the poorly aimed ones too.
+    pub aim: f32,
+    pub enemy_speed: f32,
+    /// Speed of landers carrying a humanoid up.
+    pub abduction_speed: f32,
+    /// Enemies spawned per wave.
+    pub enemy_count: f32,
+}
+
+impl Default for Scale {
+    fn default() -> Self {
+        Self {
+            shot_delay: 1.0,
+            orb_speed: 1.0,
+            aim: 0.25,
+            enemy_speed: 1.0,
+            abduction_speed: 1.0,
+            enemy_count: 1.0,
+        }
+    }
+}
+
+impl Scale {
+    /// Easier by `ease`, 1 leaves it as is.
+    fn eased(self, ease: f32) -> Self {
+        Self {
+            shot_delay: self.shot_delay / ease,
+            orb_speed: self.orb_speed * ease,
+            aim: self.aim / ease,
+            enemy_speed: self.enemy_speed * ease,
+            abduction_speed: self.abduction_speed * ease,
+            enemy_count: self.enemy_count * ease,
+        }
+    }
+
+    /// Enemies spawned for `count` at normal difficulty.
+    pub fn count(&self, count: u32) -> u32 {
+        ((count as f32 * self.enemy_count).round() as u32).max(1)
+    }
+}
+
+/// Difficulty of the run being played, taken from the settings when it
+/// starts so changing them mid-run waits for the next one.
+#[derive(Resource, Default)]
+pub struct Challenge {
+    pub difficulty: Difficulty,
+    pub adaptive: bool,
+    /// Runs lost in a row without clearing a wave.
+    pub streak: u32,
+}
+
+impl Challenge {
+    pub fn scale(&self) -> Scale {
+        let scale = self.difficulty.scale();
+        match self.adaptive {
+            true => scale.eased(ADAPTIVE_EASE.powi(self.streak.min(MAX_STREAK) as i32)),
+            false => scale,
+        }
+    }
+}
+
+pub struct Plug;
+
+impl Plugin for Plug {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Challenge>()
+            .add_systems(PostUpdate, take_settings)
+            .add_systems(Update, count_loss.run_if(attract::live))
+            .add_systems(
+                OnEnter(GameState::WaveIntro),
+                count_clear
+                    .before(enemy::spawn_enemies)
+                    .run_if(attract::live),
+            );
+    }
+}
+
+fn take_settings(
+    mut events: EventReader<NewGame>,
+    settings: Option<Res<Settings>>,
+    mut challenge: ResMut<Challenge>,
+) {
+    if events.read().last().is_none() {
+        return;
+    }
+    if let Some(settings) = settings {
+        challenge.difficulty = settings.difficulty;
+        challenge.adaptive = settings.adaptive;
+    }
+}
+
+fn count_loss(mut events: EventReader<GameOver>, mut challenge: ResMut<Challenge>) {
+    for _ in events.read() {
+        challenge.streak += 1;
+    }
+}
+
+fn count_clear(enemies: Res<EnemiesCount>, mut challenge: ResMut<Challenge>) {
+    if enemies.wave != 0 {
+        challenge.streak = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adaptive_eases_off_after_losses() {
+        let mut challenge = Challenge {
+            difficulty: Difficulty::Hard,
+            adaptive: true,
+            streak: 0,
+        };
+        let hard = challenge.scale();
+        assert_eq!(hard, Difficulty::Hard.scale());
+        challenge.streak = 2;
+        let eased = challenge.scale();
+        assert!(eased.shot_delay > hard.shot_delay && eased.orb_speed < hard.orb_speed);
+        assert!(eased.aim > hard.aim && eased.count(10) < hard.count(10));
+        challenge.streak = 10;
+        let most = challenge.scale();
+        challenge.streak = MAX_STREAK;
+        assert_eq!(most, challenge.scale());
+        challenge.adaptive = false;
+        assert_eq!(challenge.scale(), hard);
+    }
+
+    #[test]
+    fn wave_counts_scale_with_difficulty() {
+        assert_eq!(Difficulty::Normal.scale().count(7), 7);
+        assert!(Difficulty::Insane.scale().count(7) > 7);
+        assert_eq!(Difficulty::Easy.scale().count(1), 1);
+    }
+}
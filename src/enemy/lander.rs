@@ -31,8 +31,11 @@ impl Bound for Lander {
 impl Variant for Lander {
     fn data() -> VariantData {
         VariantData {
+            name: "lander",
+            points: 1,
             orb_color: utils::bevy::bloom_hue(360.0),
             shot_delay: 1.0,
+            pattern: "aimed",
             minimap_color: style::MINIMAP_ENEMY_COLOR,
         }
     }
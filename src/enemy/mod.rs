@@ -1,26 +1,40 @@
 use bevy::prelude::*;
 
 use crate::{
-    assets::{audio, GameAssets, MyTexture, MyTransform},
+    assets::{GameAssets, MyTexture, MyTransform},
+    audio::{self, Sound},
+    difficulty::Challenge,
     explosion, game_over, map, minimap,
     person::{self, Person},
-    player::{self, HORIZONTAL_SPEED},
-    projectile, score, style, utils,
+    player::{self, power_up, HORIZONTAL_SPEED},
+    projectile, rng, score, style, utils,
 };
 use game_over::GameOver;
 use player::Player;
-use score::Score;
-use utils::bevy::{hit::*, state::Simulation, window};
+use projectile::{
+    laser::Laser,
+    pattern::{self, Pattern, Shot},
+};
+use rng::GameRng;
+use score::{Score, WaveStats};
+use utils::bevy::{
+    fixed::{Interpolated, Step},
+    game_time::GameTime,
+    hit::*,
+    state::{simulating, GameState},
+    window,
+};
 
 pub mod lander;
 pub mod mutant;
+pub mod wave;
 
 #[derive(Component)]
 pub struct Enemy {
-    desired_position: Vec3,
-    next_shot: f32,
-    next_desired_position: f32,
-    last_outside: f32,
+    pub desired_position: Vec3,
+    pub next_shot: f32,
+    pub next_desired_position: f32,
+    pub last_outside: f32,
     pub person: Option<Entity>,
 }
 
@@ -30,41 +44,96 @@ pub trait Variant {
 
 #[derive(Component)]
 pub struct VariantData {
+    pub name: &'static str,
+    /// Scored for shooting it down.
+    pub points: u32,
     orb_color: Color,
     shot_delay: f32,
+    /// Name of its [`projectile::pattern::Pattern`].
+    pub pattern: &'static str,
     minimap_color: Color,
 }
 
 #[derive(Resource)]
 pub struct EnemiesCount {
     pub count: u32,
+    /// Goes up by one every wave from [`style::MIN_ENEMY_COUNT`], 0 before
+    /// the first one. Procedural waves have as many landers.
     pub wave: u32,
 }
 
+impl EnemiesCount {
+    /// Number of the current wave as shown to the player, counting from 1.
+    pub fn number(&self) -> u32 {
+        (self.wave + 1).saturating_sub(style::MIN_ENEMY_COUNT)
+    }
+}
+
 pub struct Plug;
 
 impl Plugin for Plug {
     fn build(&self, app: &mut App) {
+        let script = match utils::bevy::headless(app) {
+            true => wave::Script::default(),
+            false => wave::Script::load(),
+        };
         app.insert_resource(EnemiesCount { count: 0, wave: 0 })
+            .insert_resource(script)
+            .init_resource::<wave::Reinforcements>()
             .add_plugins(score::Plug)
             .add_systems(
-                Update,
+                FixedUpdate,
                 (
-                    movement,
-                    laser_hit,
-                    player_hit,
-                    (shoot_player, mutant_transform).after(movement),
+                    (
+                        movement,
+                        (laser_hit, player_hit, mutant_transform.after(movement))
+                            .after(projectile::beam::strike),
+                        shoot_player.after(movement),
+                        wave::trickle,
+                    )
+                        .in_set(Step::Main),
+                    wave_cleared
+                        .run_if(in_state(GameState::Playing))
+                        .in_set(Step::Late),
                 )
-                    .run_if(in_state(Simulation::Running)),
+                    .run_if(simulating),
             )
-            .add_systems(
-                PostUpdate,
-                (
-                    try_drawing_on_minimap,
-                    spawn_enemies.run_if(in_state(Simulation::Running)),
-                ),
-            );
+            .add_systems(OnEnter(GameState::WaveIntro), spawn_enemies);
+        if !utils::bevy::headless(app) {
+            app.add_systems(PostUpdate, try_drawing_on_minimap);
+        }
+    }
+}
+
+/// Whether there's a variant called `name`.
+pub fn is_variant(name: &str) -> bool {
+    [lander::Lander::data().name, mutant::Mutant::data().name].contains(&name)
+}
+
+/// Makes `entity` the variant called `name`, false if there's none.
+pub fn insert_variant(
+    entity: &mut bevy::ecs::system::EntityCommands,
+    name: &str,
+    translation: Vec3,
+    person: Option<Entity>,
+    assets: &GameAssets,
+) -> bool {
+    if name == lander::Lander::data().name {
+        entity.insert(bundle(translation, person, lander::Lander, assets));
+    } else if name == mutant::Mutant::data().name {
+        entity.insert(bundle(translation, person, mutant::Mutant, assets));
+    } else {
+        return false;
     }
+    true
+}
+
+/// Every kind of enemy with its sprite, for showing them off.
+pub fn roster(assets: &GameAssets) -> Vec<(VariantData, Handle<Image>)> {
+    vec![
+        (lander::Lander::data(), lander::Lander::texture(assets)),
+        (mutant::Mutant::data(), mutant::Mutant::texture(assets)),
+    ]
 }
 
 pub fn visible(x: f32, camera_x: f32, window_width: f32) -> bool {
@@ -73,29 +142,35 @@ pub fn visible(x: f32, camera_x: f32, window_width: f32) -> bool {
     x >= -half_screen_x && x <= half_screen_x
 }
 
+#[allow(clippy::too_many_arguments)]
 fn shoot_player(
-    mut query: Query<(&Transform, &VariantData, &mut Enemy)>,
+    mut query: Query<(Entity, &Transform, &VariantData, &mut Enemy)>,
     player_query: Query<(&Transform, &Player)>,
     window_size: Res<window::Size>,
     camera_query: Query<&Transform, With<Camera>>,
     assets: Res<GameAssets>,
     mut commands: Commands,
-    time: Res<Time>,
+    game_time: Res<GameTime>,
+    mut rng: ResMut<GameRng>,
+    mut sounds: EventWriter<audio::Play>,
+    challenge: Res<Challenge>,
 ) {
-    let elapsed = time.elapsed_seconds();
+    let elapsed = game_time.elapsed_seconds();
+    let scale = challenge.scale();
+    let orb_speed = projectile::orb::SPEED * scale.orb_speed;
     let camera_position = camera_query.single().translation;
     if let Ok((player_transform, player)) = player_query.get_single() {
         let player_position = player_transform.translation;
-        for (transform, variant, mut enemy) in query.iter_mut() {
+        for (entity, transform, variant, mut enemy) in query.iter_mut() {
             let position = transform.translation;
             let delta = player_position - position;
             let d = delta.length() / (2.5 * HORIZONTAL_SPEED);
             let dir = delta.normalize();
             let d = player_position + Vec3::X * player.horizontal_speed * d
                 - position
-                - dir * d * projectile::orb::SPEED;
+                - dir * d * orb_speed;
             let dir = Vec2::new(d.x, d.y).normalize();
-            let mut angle = Vec2::X.angle_between(dir) / 3.14 * 0.5;
+            let mut angle = Vec2::X.angle_between(dir) / std::f32::consts::PI * 0.5;
             if angle < 0.0 {
                 angle += 1.0;
             }
@@ -104,36 +179,42 @@ fn shoot_player(
             }
             let v = angle.min(1.0 - angle).min((angle - 0.5).abs()) * 4.0;
             if enemy.next_shot < elapsed && enemy.last_outside + 0.5 < elapsed {
-                if rand::random::<f32>() < v.powf(0.25) {
-                    commands.spawn(projectile::Bundle::new(
-                        &assets,
-                        position + dir.extend(0.0) * 50.0,
+                if rng.f32() < v.powf(scale.aim) {
+                    let shot = Shot {
+                        shooter: entity,
+                        origin: position,
                         angle,
-                        projectile::orb::SPEED,
-                        variant.orb_color,
-                        projectile::orb::Orb,
-                    ));
-                    commands.spawn(audio(assets.laser_audio.clone(), style::VOLUME));
+                        speed: orb_speed,
+                        color: variant.orb_color,
+                    };
+                    pattern::named(variant.pattern)
+                        .unwrap_or(Pattern::Aimed)
+                        .fire(&shot, &mut commands, &assets, elapsed);
+                    sounds.send(audio::Play::at(Sound::Laser, position));
                 }
-                enemy.next_shot = elapsed + variant.shot_delay;
+                enemy.next_shot = elapsed + variant.shot_delay * scale.shot_delay;
             }
         }
     }
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn movement(
     mut query: Query<(Entity, &mut Transform, &mut Enemy)>,
     window_size: Res<window::Size>,
     time: Res<Time>,
+    game_time: Res<GameTime>,
     map_scroll: Res<map::MapScroll>,
     mut person_query: Query<
         (Entity, &Transform, &mut person::CharacterState),
         (With<Person>, Without<Enemy>),
     >,
-    assets: Res<GameAssets>,
-    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    mut sounds: EventWriter<audio::Play>,
+    challenge: Res<Challenge>,
 ) {
-    let elapsed = time.elapsed_seconds();
+    let elapsed = game_time.elapsed_seconds();
+    let scale = challenge.scale();
     for (entity, mut transform, mut enemy) in query.iter_mut() {
         if enemy.next_desired_position < elapsed {
             let mut person_data = vec![];
@@ -151,8 +232,8 @@ fn movement(
                 .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
             let h = window_size.0.y * (1.0 - style::MINIMAP_SIZE.y);
             let offset = style::BORDER_CONFINEMENT_OFFSET;
-            let r = |factor: f32| (rand::random::<f32>() * 2.0 - 1.0) * factor;
-            let random = |position: Vec3, factor: f32| {
+            let mut r = |factor: f32| (rng.f32() * 2.0 - 1.0) * factor;
+            let mut random = |position: Vec3, factor: f32| {
                 let (dx, mut dy) = (r(factor), r(factor));
                 while dy < -position.y + offset || dy > h - position.y - offset {
                     dy = r(factor);
@@ -167,7 +248,7 @@ fn movement(
                     if d < 10.0 {
                         *data.2 = person::CharacterState::CapturedBy(entity, person::ENEMY_OFFSET);
                         enemy.person = Some(data.3);
-                        commands.spawn(audio(assets.capture_audio.clone(), style::VOLUME));
+                        sounds.send(audio::Play::at(Sound::Capture, transform.translation));
                         random(transform.translation, max_change)
                     } else {
                         random(p, if d < max_change { 0.0 } else { max_change })
@@ -191,7 +272,11 @@ fn movement(
         };
         let dy = end.y - start.y;
         let d = Vec2::new(dx * map::SIZE, dy).normalize().extend(0.0);
-        let step = 100.0 * time.delta_seconds();
+        let speed = match enemy.person {
+            Some(_) => scale.abduction_speed,
+            None => scale.enemy_speed,
+        };
+        let step = 100.0 * speed * time.delta_seconds();
         let mut p = transform.translation + d * step;
         p.x = map_scroll.update(p.x);
         transform.translation = p;
@@ -222,6 +307,7 @@ pub struct Bundle<T: Send + Sync + Component> {
     player_hit: Hittable<Player>,
     variant: T,
     variant_data: VariantData,
+    interpolated: Interpolated,
 }
 
 pub fn bundle<T: Component + MyTexture + MyTransform + Bound + Variant>(
@@ -249,88 +335,151 @@ pub fn bundle<T: Component + MyTexture + MyTransform + Bound + Variant>(
         player_hit: Hittable::new(T::bound()),
         variant,
         variant_data: T::data(),
+        interpolated: Interpolated::default(),
     }
 }
 
-fn spawn_enemies(
+fn wave_cleared(
+    enemies: Res<EnemiesCount>,
+    reinforcements: Res<wave::Reinforcements>,
+    mut commands: Commands,
+) {
+    if enemies.count == 0 && reinforcements.pending.is_empty() {
+        commands.insert_resource(NextState(Some(GameState::WaveIntro)));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_enemies(
     mut commands: Commands,
     assets: Res<GameAssets>,
     window_size: Res<window::Size>,
     camera_query: Query<&Transform, With<Camera>>,
     mut enemies: ResMut<EnemiesCount>,
-    mut score: ResMut<Score>,
     map_scroll: Res<map::MapScroll>,
-    player_query: Query<With<Player>>,
     person_query: Query<Entity, With<Person>>,
+    mut rng: ResMut<GameRng>,
+    mut sounds: EventWriter<audio::Play>,
+    challenge: Res<Challenge>,
+    script: Res<wave::Script>,
+    mut reinforcements: ResMut<wave::Reinforcements>,
+    game_time: Res<GameTime>,
 ) {
-    if enemies.count > 0 || player_query.get_single().is_err() {
-        return;
-    }
-    let camera_position = camera_query.single().translation;
-    score.value += enemies.wave * 10;
     if enemies.wave == 0 {
         enemies.wave = style::MIN_ENEMY_COUNT;
     } else {
         enemies.wave += 1;
     }
-    commands.spawn(audio(assets.begin_wave_audio.clone(), style::VOICE_VOLUME));
-    let n = person_query.iter().count();
-    score.value += 50 * n as u32;
-    for _ in 0..(8 - n) {
+    sounds.send(audio::Play::centered(Sound::BeginWave));
+    let wave = script.wave(&enemies);
+    let n = person_query.iter().count() as u32;
+    for _ in n..wave.humanoids {
         let bound = style::PERSON_BOUND.y + style::PERSON_CENTER.y;
         commands.spawn(person::bundle(
-            Vec2::new(rand::random::<f32>() * map::SIZE, bound),
+            Vec2::new(rng.f32() * map::SIZE, bound),
             person::CharacterState::Grounded,
             &assets,
         ));
     }
-    for _ in 0..enemies.wave.min(style::MAX_ENEMY_COUNT) {
-        let mut x = rand::random::<f32>() * map::SIZE;
-        x = map_scroll.update(x);
-        while visible(x, camera_position.x, window_size.0.x * 1.5) {
-            x = rand::random::<f32>() * map::SIZE;
-            x = map_scroll.update(x);
-        }
-        let y = 100.0 + rand::random::<f32>() * 400.0;
-        let position = Vec3::new(x, y, 0.0);
-        commands.spawn(bundle(position, None, lander::Lander, &assets));
-        enemies.count += 1;
-    }
+    reinforcements.pending.clear();
+    let mut spawner = wave::Spawner {
+        assets: &assets,
+        rng: &mut rng,
+        map_scroll: &map_scroll,
+        enemies: &mut enemies,
+        camera_x: camera_query.single().translation.x,
+        window_size: window_size.0,
+        now: game_time.elapsed_seconds(),
+    };
+    wave.spawn(
+        &challenge.scale(),
+        &mut commands,
+        &mut spawner,
+        &mut reinforcements,
+    );
 }
 
+#[allow(clippy::too_many_arguments)]
 fn laser_hit(
-    query: Query<(Entity, &Transform, &Hittable<projectile::laser::Laser>), With<Enemy>>,
+    query: Query<(Entity, &Transform, &Hittable<Laser>, &VariantData), With<Enemy>>,
     mut commands: Commands,
     mut score: ResMut<Score>,
     mut explosion_event: EventWriter<explosion::At>,
     mut enemies: ResMut<EnemiesCount>,
+    mut stats: ResMut<WaveStats>,
+    mut rng: ResMut<GameRng>,
+    game_time: Res<GameTime>,
 ) {
-    for (enemy_entity, enemy, hittable) in query.iter() {
-        if let Some(_) = hittable.hit_entity {
-            score.value += 1;
+    for (enemy_entity, enemy, hittable, variant) in query.iter() {
+        if hittable.hit_entity.is_some() {
+            score.value += variant.points;
+            stats.hits += 1;
+            stats.destroyed += 1;
             commands.entity(enemy_entity).despawn();
             enemies.count -= 1;
             explosion_event.send(explosion::At {
                 position: enemy.translation,
             });
+            power_up::try_dropping(
+                &mut commands,
+                &mut rng,
+                enemy.translation,
+                game_time.elapsed_seconds(),
+            );
         }
     }
 }
 
+/// Whether the enemy is shot this tick, so it doesn't also count as hitting
+/// the player or mutating.
+fn shot(laser_hit: &Hittable<Laser>) -> bool {
+    laser_hit.hit_entity.is_some()
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn player_hit(
-    query: Query<(Entity, &Transform, &Hittable<Player>), With<Enemy>>,
-    player_query: Query<&Transform, With<Player>>,
+    query: Query<
+        (
+            Entity,
+            &Transform,
+            &Hittable<Player>,
+            &Hittable<Laser>,
+            &VariantData,
+        ),
+        With<Enemy>,
+    >,
+    player_query: Query<(&Transform, &power_up::Active), With<Player>>,
     mut explosion_event: EventWriter<explosion::At>,
     mut commands: Commands,
     mut enemies: ResMut<EnemiesCount>,
     mut game_over_event: EventWriter<GameOver>,
+    mut stats: ResMut<WaveStats>,
+    mut score: ResMut<Score>,
+    mut rng: ResMut<GameRng>,
+    game_time: Res<GameTime>,
 ) {
-    for (enemy_entity, enemy_transform, hittable) in query.iter() {
+    let now = game_time.elapsed_seconds();
+    for (enemy_entity, enemy_transform, hittable, laser_hit, variant) in query.iter() {
+        if shot(laser_hit) {
+            continue;
+        }
         if let Some(player_entity) = hittable.hit_entity {
-            if let Ok(player_transform) = player_query.get(player_entity) {
+            if let Ok((player_transform, active)) = player_query.get(player_entity) {
+                if active.on(power_up::Kind::Shield, now) {
+                    // Rammed into the shield, only the enemy goes, as if shot.
+                    let position = enemy_transform.translation;
+                    commands.entity(enemy_entity).despawn();
+                    enemies.count -= 1;
+                    stats.destroyed += 1;
+                    score.value += variant.points;
+                    explosion_event.send(explosion::At { position });
+                    power_up::try_dropping(&mut commands, &mut rng, position, now);
+                    continue;
+                }
                 commands.entity(player_entity).despawn();
                 commands.entity(enemy_entity).despawn();
                 enemies.count -= 1;
+                stats.destroyed += 1;
                 explosion_event.send(explosion::At {
                     position: player_transform.translation,
                 });
@@ -345,14 +494,17 @@ fn player_hit(
 }
 
 fn mutant_transform(
-    query: Query<(Entity, &Transform, &Enemy)>,
+    query: Query<(Entity, &Transform, &Enemy, &Hittable<Laser>)>,
     assets: Res<GameAssets>,
     mut commands: Commands,
     window_size: Res<window::Size>,
 ) {
     let h = window_size.0.y * (1.0 - style::MINIMAP_SIZE.y);
     let offset = style::BORDER_CONFINEMENT_OFFSET;
-    for (entity, transform, enemy) in query.iter() {
+    for (entity, transform, enemy, laser_hit) in query.iter() {
+        if shot(laser_hit) {
+            continue;
+        }
         let position = transform.translation;
         if let Some(person_entity) = enemy.person {
             if position.y > h - (offset + 1.0) {
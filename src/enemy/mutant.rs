@@ -31,8 +31,11 @@ impl Bound for Mutant {
 impl Variant for Mutant {
     fn data() -> VariantData {
         VariantData {
+            name: "mutant",
+            points: 1,
             orb_color: utils::bevy::bloom_hue(120.0),
             shot_delay: 0.25,
+            pattern: "aimed",
             minimap_color: style::MINIMAP_MUTANT_COLOR,
         }
     }
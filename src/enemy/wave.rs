@@ -0,0 +1,348 @@
+use super::{insert_variant, is_variant, lander::Lander, visible, EnemiesCount, Variant};
+use crate::{
+    assets::GameAssets,
+    difficulty::Scale,
+    map,
+    rng::GameRng,
+    style,
+    utils::bevy::{game_time::GameTime, window},
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const PATH: &str = "assets/waves.json";
+/// Humanoids on the ground at the start of a wave, unless it says otherwise.
+const HUMANOIDS: u32 = 8;
+
+/// Waves played in order, procedural ones follow once they run out.
+#[derive(Resource, Default, Deserialize)]
+pub struct Script {
+    waves: Vec<Wave>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct Wave {
+    groups: Vec<Group>,
+    /// Topped up to this many at the start of the wave.
+    #[serde(default = "humanoids")]
+    pub humanoids: u32,
+    /// Paid for clearing the wave, by default [`style::WAVE_BONUS`] times
+    /// [`EnemiesCount::wave`].
+    bonus: Option<u32>,
+}
+
+fn humanoids() -> u32 {
+    HUMANOIDS
+}
+
+#[derive(Clone, Deserialize)]
+struct Group {
+    variant: String,
+    count: u32,
+    #[serde(default)]
+    timing: Timing,
+    #[serde(default)]
+    zone: Zone,
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Timing {
+    #[default]
+    AllAtOnce,
+    /// One every `interval` seconds.
+    Trickle { interval: f32 },
+}
+
+/// Where the enemies of a group show up.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Zone {
+    /// Anywhere out of sight.
+    #[default]
+    Anywhere,
+    /// Between two fractions of the map width.
+    Span { from: f32, to: f32 },
+    /// In a row from a fraction of the map width, at height `y`.
+    Line { x: f32, y: f32, spacing: f32 },
+}
+
+impl Zone {
+    /// Position of the `i`th enemy of a group, always out of sight and in
+    /// the band enemies fly in.
+    fn position(
+        &self,
+        i: u32,
+        rng: &mut GameRng,
+        map_scroll: &map::MapScroll,
+        camera_x: f32,
+        window_size: Vec2,
+    ) -> Vec3 {
+        let y = 100.0 + rng.f32() * 400.0;
+        let (x, y) = match *self {
+            Zone::Anywhere => {
+                let mut x = map_scroll.update(rng.f32() * map::SIZE);
+                while visible(x, camera_x, window_size.x * 1.5) {
+                    x = map_scroll.update(rng.f32() * map::SIZE);
+                }
+                (x, y)
+            }
+            Zone::Span { from, to } => ((from + rng.f32() * (to - from)) * map::SIZE, y),
+            Zone::Line { x, y, spacing } => (x * map::SIZE + i as f32 * spacing, y),
+        };
+        let x = out_of_sight(map_scroll.update(x), camera_x, window_size.x);
+        let (bottom, top) = map::band(window_size);
+        Vec3::new(map_scroll.update(x), y.clamp(bottom, top), 0.0)
+    }
+}
+
+/// `x` moved on by the width of the sight if it's in it, away from the
+/// camera, so enemies placed in view keep their spacing just out of it.
+fn out_of_sight(x: f32, camera_x: f32, window_width: f32) -> f32 {
+    let sight = window_width * 1.5;
+    match visible(x, camera_x, sight) {
+        true => x + (x - camera_x).signum() * sight,
+        false => x,
+    }
+}
+
+/// An enemy of the wave still to come.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Pending {
+    pub variant: String,
+    /// Game time it shows up at.
+    pub due: f32,
+    pub zone: Zone,
+    pub index: u32,
+}
+
+/// Trickled enemies of the current wave, the wave isn't cleared before
+/// they all showed up.
+#[derive(Resource, Default)]
+pub struct Reinforcements {
+    pub pending: Vec<Pending>,
+}
+
+pub enum Error {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Variant(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "io: {}", error),
+            Error::Json(error) => write!(f, "json: {}", error),
+            Error::Variant(name) => write!(f, "unknown enemy {}", name),
+        }
+    }
+}
+
+impl Script {
+    pub fn load() -> Self {
+        #[cfg(not(target_family = "wasm"))]
+        match Self::read(PATH) {
+            Ok(script) => return script,
+            Err(error) => warn!("invalid wave script, waves are procedural: {}", error),
+        }
+        Self::default()
+    }
+
+    fn read(path: &str) -> Result<Self, Error> {
+        let input = std::fs::read_to_string(path).map_err(Error::Io)?;
+        let script: Self = serde_json::from_str(&input).map_err(Error::Json)?;
+        let groups = script.waves.iter().flat_map(|wave| &wave.groups);
+        if let Some(group) = groups.clone().find(|group| !is_variant(&group.variant)) {
+            return Err(Error::Variant(group.variant.clone()));
+        }
+        Ok(script)
+    }
+
+    /// The wave `enemies` is at.
+    pub fn wave(&self, enemies: &EnemiesCount) -> Wave {
+        let i = enemies.number().saturating_sub(1) as usize;
+        match self.waves.get(i) {
+            Some(wave) => wave.clone(),
+            None => Wave::procedural(enemies.wave),
+        }
+    }
+}
+
+impl Wave {
+    /// `wave` landers at once, anywhere out of sight.
+    fn procedural(wave: u32) -> Self {
+        Self {
+            groups: vec![Group {
+                variant: Lander::data().name.to_string(),
+                count: wave.min(style::MAX_ENEMY_COUNT),
+                timing: Timing::AllAtOnce,
+                zone: Zone::Anywhere,
+            }],
+            humanoids: HUMANOIDS,
+            bonus: None,
+        }
+    }
+
+    pub fn bonus(&self, enemies: &EnemiesCount) -> u32 {
+        self.bonus.unwrap_or(enemies.wave * style::WAVE_BONUS)
+    }
+
+    /// Spawns the enemies due at once, queues up the trickled ones.
+    pub fn spawn(
+        &self,
+        scale: &Scale,
+        commands: &mut Commands,
+        spawner: &mut Spawner,
+        reinforcements: &mut Reinforcements,
+    ) {
+        for group in &self.groups {
+            for i in 0..scale.count(group.count) {
+                match group.timing {
+                    Timing::AllAtOnce => {
+                        spawner.spawn(commands, &group.variant, group.zone, i);
+                    }
+                    Timing::Trickle { interval } => reinforcements.pending.push(Pending {
+                        variant: group.variant.clone(),
+                        due: spawner.now + interval * i as f32,
+                        zone: group.zone,
+                        index: i,
+                    }),
+                }
+            }
+        }
+    }
+}
+
+/// What placing an enemy takes.
+pub struct Spawner<'a> {
+    pub assets: &'a GameAssets,
+    pub rng: &'a mut GameRng,
+    pub map_scroll: &'a map::MapScroll,
+    pub enemies: &'a mut EnemiesCount,
+    pub camera_x: f32,
+    pub window_size: Vec2,
+    pub now: f32,
+}
+
+impl Spawner<'_> {
+    fn spawn(&mut self, commands: &mut Commands, variant: &str, zone: Zone, i: u32) {
+        let position = zone.position(
+            i,
+            self.rng,
+            self.map_scroll,
+            self.camera_x,
+            self.window_size,
+        );
+        let mut entity = commands.spawn_empty();
+        insert_variant(&mut entity, variant, position, None, self.assets);
+        self.enemies.count += 1;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn trickle(
+    mut commands: Commands,
+    mut reinforcements: ResMut<Reinforcements>,
+    assets: Res<GameAssets>,
+    mut rng: ResMut<GameRng>,
+    map_scroll: Res<map::MapScroll>,
+    mut enemies: ResMut<EnemiesCount>,
+    camera_query: Query<&Transform, With<Camera>>,
+    window_size: Res<window::Size>,
+    game_time: Res<GameTime>,
+) {
+    let now = game_time.elapsed_seconds();
+    if reinforcements
+        .pending
+        .iter()
+        .all(|pending| pending.due > now)
+    {
+        return;
+    }
+    let mut spawner = Spawner {
+        assets: &assets,
+        rng: &mut rng,
+        map_scroll: &map_scroll,
+        enemies: &mut enemies,
+        camera_x: camera_query.single().translation.x,
+        window_size: window_size.0,
+        now,
+    };
+    reinforcements.pending.retain(|pending| {
+        let due = pending.due <= now;
+        if due {
+            spawner.spawn(&mut commands, &pending.variant, pending.zone, pending.index);
+        }
+        !due
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_script_is_valid() {
+        let script = Script::read(PATH).unwrap_or_else(|error| panic!("{}", error));
+        assert!(!script.waves.is_empty());
+    }
+
+    #[test]
+    fn procedural_past_the_script() {
+        let script = Script {
+            waves: vec![Wave {
+                groups: vec![],
+                humanoids: 2,
+                bonus: Some(500),
+            }],
+        };
+        let first = EnemiesCount {
+            count: 0,
+            wave: style::MIN_ENEMY_COUNT,
+        };
+        let wave = script.wave(&first);
+        assert_eq!((wave.humanoids, wave.bonus(&first)), (2, 500));
+        let second = EnemiesCount {
+            count: 0,
+            wave: style::MIN_ENEMY_COUNT + 1,
+        };
+        let wave = script.wave(&second);
+        assert_eq!(wave.humanoids, HUMANOIDS);
+        assert_eq!(wave.groups[0].count, style::MIN_ENEMY_COUNT + 1);
+        assert_eq!(wave.bonus(&second), second.wave * style::WAVE_BONUS);
+    }
+
+    #[test]
+    fn zones_in_view_spawn_out_of_it() {
+        let window_size = Vec2::new(1280.0, 720.0);
+        let camera_x = map::SIZE * 0.5;
+        let map_scroll = map::MapScroll::new(camera_x);
+        let mut rng = GameRng::new(Some(1));
+        let zones = [
+            Zone::Span {
+                from: 0.49,
+                to: 0.51,
+            },
+            Zone::Line {
+                x: 0.5,
+                y: 5000.0,
+                spacing: 60.0,
+            },
+        ];
+        for zone in zones {
+            for i in 0..4 {
+                let position = zone.position(i, &mut rng, &map_scroll, camera_x, window_size);
+                assert!(!visible(position.x, camera_x, window_size.x));
+                assert!(position.y < window_size.y * (1.0 - style::MINIMAP_SIZE.y));
+            }
+        }
+        let line = Zone::Line {
+            x: 0.5,
+            y: 300.0,
+            spacing: 60.0,
+        };
+        let mut at = |i| line.position(i, &mut rng, &map_scroll, camera_x, window_size);
+        assert_eq!(at(1).x - at(0).x, 60.0);
+    }
+}
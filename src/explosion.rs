@@ -1,6 +1,7 @@
 use crate::{
-    assets::{audio, GameAssets, MyTexture, MyTransform},
-    projectile, style, utils,
+    assets::{GameAssets, MyTexture, MyTransform},
+    audio::{self, Sound},
+    projectile, rng, style, utils,
 };
 use bevy::prelude::*;
 
@@ -13,7 +14,7 @@ pub struct Plug;
 
 impl Plugin for Plug {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, try_spawning);
+        app.add_event::<At>().add_systems(Update, try_spawning);
     }
 }
 
@@ -36,20 +37,19 @@ impl MyTexture for Explosion {
 fn try_spawning(
     mut commands: Commands,
     assets: Res<GameAssets>,
-    time: Res<Time>,
+    time: Res<utils::bevy::game_time::GameTime>,
     mut event: EventReader<At>,
+    mut rng: ResMut<rng::CosmeticRng>,
+    mut sounds: EventWriter<audio::Play>,
 ) {
     for explosion in event.read() {
-        commands.spawn(audio(
-            assets.collision_audio.clone(),
-            style::EXPLOSION_VOLUME,
-        ));
+        sounds.send(audio::Play::at(Sound::Collision, explosion.position));
         let n = 16;
         let speed = 400.0;
         for i in 0..n {
             let angle = i as f32 / (n - 1) as f32;
             let clock = utils::bevy::clock(angle).extend(0.0);
-            let hue = rand::random::<f32>() * 360.0;
+            let hue = rng.f32() * 360.0;
             commands.spawn((
                 projectile::Bundle::new(
                     &assets,
@@ -1,51 +1,101 @@
 use crate::{
-    assets::{audio, GameAssets},
+    audio::{self, Sound},
     high_scores::*,
+    new_game::NewGame,
+    rng::GameRng,
     score::Score,
     style, utils,
 };
 use bevy::prelude::*;
-use utils::bevy::state::Simulation;
+use utils::bevy::state::{Countdown, GameState};
 
+/// Sent when the player is destroyed.
 #[derive(Event)]
 pub struct GameOver;
 
+/// How long the world keeps going after the player is destroyed.
+const DEATH_TIME: f32 = 0.5;
+/// How long "GAME OVER" is shown before moving on.
+const GAME_OVER_TIME: f32 = 2.0;
+
 #[derive(Component)]
-pub struct ChangeState {
-    pub elapsed: f32,
-}
+struct GameOverText;
 
 pub struct Plug;
 
 impl Plugin for Plug {
     fn build(&self, app: &mut App) {
         app.add_event::<GameOver>()
-            .add_systems(Update, (listen_for_game_over, change_state));
+            .add_systems(Update, listen_for_game_over)
+            .add_systems(OnEnter(GameState::PlayerDeath), start_dying)
+            .add_systems(OnEnter(GameState::GameOver), move_on);
+        if !utils::bevy::headless(app) {
+            app.add_systems(Startup, spawn_game_over_text)
+                .add_systems(Update, hide_game_over_text)
+                .add_systems(OnEnter(GameState::GameOver), show_game_over_text);
+        }
     }
 }
 
 fn listen_for_game_over(
     mut event: EventReader<GameOver>,
     mut commands: Commands,
-    assets: Res<GameAssets>,
-    time: Res<Time>,
-    score: Res<Score>,
-    mut high_scores: ResMut<HighScores>,
+    mut sounds: EventWriter<audio::Play>,
 ) {
     for _ in event.read() {
-        high_scores.save(score.value);
-        commands.spawn(audio(assets.game_over_audio.clone(), style::VOICE_VOLUME));
-        commands.spawn(ChangeState {
-            elapsed: time.elapsed_seconds(),
-        });
+        sounds.send(audio::Play::centered(Sound::GameOver));
+        commands.insert_resource(NextState(Some(GameState::PlayerDeath)));
     }
 }
 
-fn change_state(mut commands: Commands, query: Query<(Entity, &ChangeState)>, time: Res<Time>) {
-    if let Ok((entity, change_state)) = query.get_single() {
-        if change_state.elapsed + 0.5 < time.elapsed_seconds() {
-            commands.insert_resource(NextState(Some(Simulation::Paused)));
-            commands.entity(entity).despawn();
-        }
+fn start_dying(mut commands: Commands) {
+    commands.insert_resource(Countdown::new(DEATH_TIME, GameState::GameOver));
+}
+
+fn show_game_over_text(
+    mut query: Query<(&mut Text, &mut Style), With<GameOverText>>,
+    rng: Res<GameRng>,
+) {
+    let (mut text, mut style) = query.single_mut();
+    text.sections[1].value = format!("seed {}", rng.seed());
+    style.display = Display::Flex;
+}
+
+fn move_on(mut commands: Commands, score: Res<Score>, high_scores: Res<HighScores>) {
+    let next = match high_scores.qualifies(score.value) {
+        true => GameState::HighScoreEntry,
+        false => GameState::Title,
+    };
+    commands.insert_resource(Countdown::new(GAME_OVER_TIME, next));
+}
+
+fn spawn_game_over_text(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let style = |font_size| TextStyle {
+        font: asset_server.load(style::FONT),
+        font_size,
+        color: style::SCORE_COLOR,
+    };
+    commands.spawn((
+        TextBundle::from_sections([
+            TextSection::new("GAME OVER\n", style(style::SCORE_FONT_SIZE)),
+            TextSection::from_style(style(24.0)),
+        ])
+        .with_style(Style {
+            display: Display::None,
+            position_type: PositionType::Absolute,
+            top: Val::Percent(style::MINIMAP_SIZE.y * 100.0),
+            right: Val::Px(15.0),
+            ..default()
+        }),
+        GameOverText,
+    ));
+}
+
+fn hide_game_over_text(
+    mut new_game_event: EventReader<NewGame>,
+    mut query: Query<&mut Style, With<GameOverText>>,
+) {
+    for _ in new_game_event.read() {
+        query.single_mut().display = Display::None;
     }
 }
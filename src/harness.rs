@@ -0,0 +1,266 @@
+//! The game without a window, assets or audio, stepped one fixed tick per
+//! frame with scripted controls.
+
+use crate::{
+    assets::{self, GameAssets},
+    audio, autopilot, camera, difficulty,
+    enemy::{self, EnemiesCount, Enemy},
+    explosion, game_over,
+    high_scores::HighScores,
+    map, new_game,
+    person::{self, CharacterState, Person},
+    player::{self, input::Controls, Player},
+    projectile, rng,
+    score::Score,
+    utils, wave_intro,
+};
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use std::time::Duration;
+use utils::bevy::{hit::Bound, state::GameState, window, Headless};
+
+pub const WINDOW_SIZE: Vec2 = Vec2::new(1280.0, 720.0);
+/// One frame is one tick at the default tick rate.
+pub const FRAME: Duration = Duration::from_nanos(1_000_000_000 / 64);
+pub const FRAMES_PER_SECOND: usize = 64;
+
+pub struct Harness {
+    pub app: App,
+}
+
+impl Harness {
+    pub fn new(window_size: Vec2) -> Self {
+        let mut app = App::new();
+        app.insert_resource(Headless)
+            .add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME))
+            .insert_resource(window::Size(window_size))
+            .init_resource::<GameAssets>()
+            .init_resource::<HighScores>()
+            .add_plugins((
+                utils::bevy::Plug,
+                audio::Plug,
+                new_game::Plug,
+                wave_intro::Plug,
+                map::Plug,
+                player::Plug,
+                enemy::Plug,
+                person::Plug,
+                projectile::Plug,
+                explosion::Plug,
+                game_over::Plug,
+                rng::Plug { seed: None },
+                autopilot::Plug,
+                difficulty::Plug,
+            ))
+            .add_systems(Startup, camera::spawn)
+            .add_systems(Update, camera::window_height_center);
+        app.update();
+        Self { app }
+    }
+
+    /// Starts a run from `seed` and waits out the intro of the first wave.
+    pub fn start(&mut self, seed: u64) {
+        self.app.world.send_event(new_game::NewGame { seed });
+        // Steps once first, a run in progress is still playing until then.
+        self.step(1, Controls::default());
+        let playing = self.run_until(10.0, Controls::default(), |harness| {
+            harness.state() == GameState::Playing
+        });
+        assert!(playing, "the first wave never started");
+    }
+
+    pub fn step(&mut self, frames: usize, controls: Controls) {
+        *self.app.world.resource_mut::<Controls>() = controls;
+        for _ in 0..frames {
+            self.app.update();
+        }
+    }
+
+    /// Steps until `done` or `seconds` pass, returns whether it got done.
+    pub fn run_until(
+        &mut self,
+        seconds: f32,
+        controls: Controls,
+        done: impl Fn(&mut Self) -> bool,
+    ) -> bool {
+        let frames = (seconds * FRAMES_PER_SECOND as f32) as usize;
+        for _ in 0..frames {
+            if done(self) {
+                return true;
+            }
+            self.step(1, controls);
+        }
+        done(self)
+    }
+
+    pub fn state(&self) -> GameState {
+        *self.app.world.resource::<State<GameState>>().get()
+    }
+
+    pub fn score(&self) -> u32 {
+        self.app.world.resource::<Score>().value
+    }
+
+    /// Takes every enemy and humanoid of the wave away, for scenarios to
+    /// place their own.
+    pub fn clear(&mut self) {
+        let world = &mut self.app.world;
+        let entities: Vec<Entity> = world
+            .query_filtered::<Entity, Or<(With<Enemy>, With<Person>)>>()
+            .iter(world)
+            .collect();
+        for entity in entities {
+            world.despawn(entity);
+        }
+        world.resource_mut::<EnemiesCount>().count = 0;
+        world
+            .resource_mut::<enemy::wave::Reinforcements>()
+            .pending
+            .clear();
+    }
+
+    pub fn spawn_enemy<T>(&mut self, position: Vec3, variant: T, person: Option<Entity>) -> Entity
+    where
+        T: Component + assets::MyTexture + assets::MyTransform + Bound + enemy::Variant,
+    {
+        let assets = GameAssets::default();
+        let world = &mut self.app.world;
+        world.resource_mut::<EnemiesCount>().count += 1;
+        world
+            .spawn(enemy::bundle(position, person, variant, &assets))
+            .id()
+    }
+
+    pub fn spawn_person(&mut self, position: Vec2, state: CharacterState) -> Entity {
+        let assets = GameAssets::default();
+        self.app
+            .world
+            .spawn(person::bundle(position, state, &assets))
+            .id()
+    }
+
+    pub fn player(&mut self) -> Option<(Entity, Vec3)> {
+        let world = &mut self.app.world;
+        world
+            .query_filtered::<(Entity, &Transform), With<Player>>()
+            .get_single(world)
+            .ok()
+            .map(|(entity, transform)| (entity, transform.translation))
+    }
+
+    pub fn count<T: Component>(&mut self) -> usize {
+        let world = &mut self.app.world;
+        world.query::<&T>().iter(world).count()
+    }
+
+    pub fn person_state(&self, person: Entity) -> Option<&CharacterState> {
+        self.app.world.get::<CharacterState>(person)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style;
+    use enemy::{lander::Lander, mutant::Mutant};
+
+    fn started() -> Harness {
+        let mut harness = Harness::new(WINDOW_SIZE);
+        harness.start(7);
+        harness.clear();
+        harness
+    }
+
+    fn ground() -> f32 {
+        style::PERSON_BOUND.y + style::PERSON_CENTER.y
+    }
+
+    /// Somewhere the player can't see, so nothing shoots at it.
+    fn far_away() -> f32 {
+        map::SIZE * 0.5
+    }
+
+    #[test]
+    fn lander_captures_grounded_humanoid() {
+        let mut harness = started();
+        let person =
+            harness.spawn_person(Vec2::new(far_away(), ground()), CharacterState::Grounded);
+        let above = Vec3::new(far_away(), ground(), 0.0) - person::ENEMY_OFFSET.extend(0.0);
+        let lander = harness.spawn_enemy(above, Lander, None);
+
+        let captured = harness.run_until(1.0, Controls::default(), |harness| {
+            matches!(
+                harness.person_state(person),
+                Some(CharacterState::CapturedBy(captor, _)) if *captor == lander
+            )
+        });
+        assert!(captured);
+        let world = &harness.app.world;
+        assert_eq!(world.get::<Enemy>(lander).unwrap().person, Some(person));
+    }
+
+    #[test]
+    fn lander_mutates_at_the_top() {
+        let mut harness = started();
+        let position = Vec2::new(far_away(), 300.0);
+        let person = harness.spawn_person(position, CharacterState::Grounded);
+        let lander = harness.spawn_enemy(position.extend(0.0), Lander, Some(person));
+        *harness.app.world.get_mut::<CharacterState>(person).unwrap() =
+            CharacterState::CapturedBy(lander, person::ENEMY_OFFSET);
+
+        let mutated = harness.run_until(20.0, Controls::default(), |harness| {
+            harness.count::<Mutant>() == 1
+        });
+        assert!(mutated);
+        assert_eq!(harness.count::<Lander>(), 0);
+        assert_eq!(harness.count::<Person>(), 0);
+        assert_eq!(harness.app.world.resource::<EnemiesCount>().count, 1);
+        assert_eq!(harness.state(), GameState::Playing);
+    }
+
+    #[test]
+    fn player_rescues_falling_humanoid() {
+        let mut harness = started();
+        harness.spawn_enemy(Vec3::new(far_away(), 300.0, 0.0), Lander, None);
+        let (player, position) = harness.player().unwrap();
+        let person = harness.spawn_person(position.truncate(), CharacterState::Falling);
+        let score = harness.score();
+
+        let rescue = Controls {
+            rescue: true,
+            ..default()
+        };
+        let rescued = harness.run_until(1.0, rescue, |harness| {
+            matches!(
+                harness.person_state(person),
+                Some(CharacterState::CapturedBy(captor, _)) if *captor == player
+            )
+        });
+        assert!(rescued);
+        assert_eq!(harness.score(), score + 100);
+    }
+
+    #[test]
+    fn colliding_with_lander_ends_the_game() {
+        let mut harness = started();
+        harness.spawn_enemy(Vec3::new(far_away(), 300.0, 0.0), Lander, None);
+        let (_, position) = harness.player().unwrap();
+        harness.spawn_enemy(position, Lander, None);
+
+        let dead = harness.run_until(1.0, Controls::default(), |harness| {
+            harness.state() == GameState::PlayerDeath
+        });
+        assert!(dead);
+        assert!(harness.player().is_none());
+        assert_eq!(harness.app.world.resource::<EnemiesCount>().count, 1);
+
+        let over = harness.run_until(1.0, Controls::default(), |harness| {
+            harness.state() == GameState::GameOver
+        });
+        assert!(over);
+        let back = harness.run_until(3.0, Controls::default(), |harness| {
+            harness.state() == GameState::Title
+        });
+        assert!(back, "a run without points goes back to the title");
+    }
+}
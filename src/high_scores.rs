@@ -1,66 +1,163 @@
 use crate::{
+    difficulty::{Challenge, Difficulty},
+    score::Score,
     style,
     utils::{self, bevy::window},
 };
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::f32::consts::TAU;
-use utils::bevy::state::Simulation;
+use utils::bevy::state::{Countdown, GameState};
+
+/// How long a new high score is shown before going back to the title.
+const ENTRY_TIME: f32 = 4.0;
 
 pub struct Plug;
 
 impl Plugin for Plug {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, (load_high_scores, spawn_high_score_text).chain())
-            .add_systems(Update, (update_high_score_text, toggle_visibility));
+            .add_systems(Update, update_high_score_text)
+            .add_systems(OnEnter(GameState::HighScoreEntry), enter_score);
+        for state in [
+            GameState::Title,
+            GameState::Paused,
+            GameState::HighScoreEntry,
+        ] {
+            app.add_systems(OnEnter(state), show)
+                .add_systems(OnExit(state), hide);
+        }
     }
 }
 
 fn load_high_scores(mut commands: Commands) {
-    commands.insert_resource(HighScores::load());
+    let high_scores = HighScores::load().unwrap_or_else(|error| {
+        warn!("invalid high scores, starting empty: {}", error);
+        HighScores::default()
+    });
+    commands.insert_resource(high_scores);
 }
 
-#[derive(Resource)]
+#[derive(Resource, Default, PartialEq, Debug, Serialize, Deserialize)]
 pub struct HighScores {
-    scores: Vec<u32>,
+    scores: Vec<Entry>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Entry {
+    pub score: u32,
+    /// Unix seconds when the score was recorded, 0 if unknown.
+    pub time: u64,
+    /// Normal for scores from before difficulties.
+    #[serde(default)]
+    pub difficulty: Difficulty,
 }
 
+impl Entry {
+    pub fn now(score: u32, difficulty: Difficulty) -> Self {
+        #[cfg(not(target_family = "wasm"))]
+        let time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        #[cfg(target_family = "wasm")]
+        let time = 0;
+        Self {
+            score,
+            time,
+            difficulty,
+        }
+    }
+}
+
+#[derive(Debug)]
 pub enum Error {
     Score(std::num::ParseIntError),
+    Time(std::num::ParseIntError),
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Difficulty(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Score(error) => write!(f, "score: {}", error),
+            Error::Time(error) => write!(f, "time: {}", error),
+            Error::Io(error) => write!(f, "io: {}", error),
+            Error::Json(error) => write!(f, "json: {}", error),
+            Error::Difficulty(name) => write!(f, "difficulty: {}", name),
+        }
+    }
 }
 
 const PATH: &str = "assets/high_scores.txt";
 
 impl HighScores {
-    pub fn load() -> Self {
+    pub fn load() -> Result<Self, Error> {
         let empty = "".to_string();
         #[cfg(not(target_family = "wasm"))]
         let input = std::fs::read_to_string(PATH).unwrap_or(empty);
         #[cfg(target_family = "wasm")]
         let input = empty;
-        match Self::read(input) {
-            Ok(scores) => scores,
-            Err(Error::Score(parse_error)) => {
-                panic!("invalid high scores: {:?}", parse_error)
-            }
-        }
+        Self::read(input)
+    }
+
+    /// Whether `score` would make it into the table.
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0 && (self.scores.len() < 10 || self.scores.iter().any(|entry| entry.score < score))
     }
 
-    pub fn save(&mut self, new: u32) {
-        self.scores.push(new);
+    pub fn save(&mut self, new: u32, difficulty: Difficulty) {
+        self.scores.push(Entry::now(new, difficulty));
         self.top10_ordered();
+        self.store();
+    }
+
+    fn store(&self) {
         #[cfg(not(target_family = "wasm"))]
         let _ = std::fs::write(PATH, self.write());
     }
 
+    pub fn export(&self, path: &str) -> Result<(), Error> {
+        let json = serde_json::to_string_pretty(self).map_err(Error::Json)?;
+        std::fs::write(path, json).map_err(Error::Io)
+    }
+
+    /// Merges the table at `path` into this one and stores the result.
+    pub fn import(&mut self, path: &str) -> Result<(), Error> {
+        self.merge(Self::open(path)?);
+        self.store();
+        Ok(())
+    }
+
+    /// The table exported to `path`.
+    fn open(path: &str) -> Result<Self, Error> {
+        let json = std::fs::read_to_string(path).map_err(Error::Io)?;
+        serde_json::from_str(&json).map_err(Error::Json)
+    }
+
+    fn merge(&mut self, other: Self) {
+        self.scores.extend(other.scores);
+        self.top10_ordered();
+    }
+
     fn read(input: String) -> Result<Self, Error> {
         let mut read = Reader::new(input.as_str());
         let mut scores = vec![];
         while !read.ended() {
-            let score = read
-                .till(|c| c != '\n')
-                .parse::<u32>()
-                .map_err(Error::Score)?;
-            scores.push(score);
+            let line = read.till(|c| c != '\n');
+            let mut fields = line.split(' ');
+            let mut next = || fields.next().unwrap_or("0");
+            let score = next().parse().map_err(Error::Score)?;
+            let time = next().parse().map_err(Error::Time)?;
+            let difficulty = fields.next().map_or(Ok(Difficulty::Normal), |name| {
+                Difficulty::from_name(name).ok_or(Error::Difficulty(name.to_string()))
+            })?;
+            scores.push(Entry {
+                score,
+                time,
+                difficulty,
+            });
         }
         let mut high_scores = Self { scores };
         high_scores.top10_ordered();
@@ -69,15 +166,37 @@ impl HighScores {
 
     fn write(&self) -> String {
         let mut output = String::new();
-        for score in &self.scores {
-            output.extend(format!("{:06}", score).chars());
+        for entry in &self.scores {
+            let line = format!(
+                "{:06} {} {}",
+                entry.score,
+                entry.time,
+                entry.difficulty.name()
+            );
+            output.push_str(&line);
+            output.push('\n');
+        }
+        output
+    }
+
+    fn text(&self) -> String {
+        let mut output = String::new();
+        for entry in &self.scores {
+            let line = format!("{:06} {}", entry.score, entry.difficulty.name());
+            output.push_str(&line);
             output.push('\n');
         }
         output
     }
 
     fn top10_ordered(&mut self) {
-        self.scores.sort_by(|a, b| b.cmp(a));
+        self.scores.sort_by(|a, b| {
+            (b.score.cmp(&a.score))
+                .then(a.time.cmp(&b.time))
+                .then(a.difficulty.cmp(&b.difficulty))
+        });
+        // Entries of unknown time can't be told apart, so they all stay.
+        self.scores.dedup_by(|a, b| a.time != 0 && a == b);
         while self.scores.len() > 10 {
             self.scores.pop();
         }
@@ -97,7 +216,7 @@ impl<'a> Reader<'a> {
 
     pub fn till(&mut self, satisfy: impl Fn(char) -> bool) -> String {
         let mut output = String::new();
-        while let Some(c) = self.it.next() {
+        for c in self.it.by_ref() {
             if satisfy(c) {
                 output.push(c);
             } else {
@@ -113,9 +232,7 @@ impl<'a> Reader<'a> {
 }
 
 #[derive(Component)]
-struct HighScoreText;
-
-const TEXT_SPACE: f32 = 0.5 * (1.0 - style::MINIMAP_SIZE.x);
+pub struct HighScoreText;
 
 fn spawn_high_score_text(mut commands: Commands, asset_server: Res<AssetServer>) {
     let font_size = style::SCORE_FONT_SIZE;
@@ -142,13 +259,17 @@ fn update_high_score_text(
     high_scores: Res<HighScores>,
     time: Res<Time>,
     window_size: Res<window::Size>,
+    state: Res<State<GameState>>,
 ) {
     let (mut text, mut style) = query.single_mut();
     let font_size = style::SCORE_FONT_SIZE;
     style.top = Val::Px(window_size.0.y * style::MINIMAP_SIZE.y + font_size * 0.5);
-    style.right = Val::Px(window_size.0.x * (1.0 - TEXT_SPACE) + 15.0);
-    text.sections[0].value = format!("TOP {}\n", high_scores.scores.len());
-    text.sections[1].value = high_scores.write();
+    style.right = Val::Px(window_size.0.x * (1.0 - style::TEXT_SPACE) + 15.0);
+    text.sections[0].value = match state.get() {
+        GameState::HighScoreEntry => "NEW HIGH SCORE\n".to_string(),
+        _ => format!("TOP {}\n", high_scores.scores.len()),
+    };
+    text.sections[1].value = high_scores.text();
     text.sections[1].style.color = Color::Hsla {
         hue: (time.elapsed_seconds() / 6.0 * HZ).fract() * 360.0,
         saturation: 1.0,
@@ -157,13 +278,113 @@ fn update_high_score_text(
     };
 }
 
-fn toggle_visibility(
-    state: Res<State<Simulation>>,
-    mut query: Query<&mut Style, With<HighScoreText>>,
+fn enter_score(
+    mut commands: Commands,
+    mut high_scores: ResMut<HighScores>,
+    score: Res<Score>,
+    challenge: Res<Challenge>,
 ) {
-    let mut style = query.get_single_mut().unwrap();
-    match state.get() {
-        Simulation::Paused => style.display = Display::Flex,
-        Simulation::Running => style.display = Display::None,
+    high_scores.save(score.value, challenge.difficulty);
+    commands.insert_resource(Countdown::new(ENTRY_TIME, GameState::Title));
+}
+
+fn show(mut query: Query<&mut Style, With<HighScoreText>>) {
+    query.single_mut().display = Display::Flex;
+}
+
+fn hide(mut query: Query<&mut Style, With<HighScoreText>>) {
+    query.single_mut().display = Display::None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(score: u32, time: u64) -> Entry {
+        Entry {
+            score,
+            time,
+            difficulty: Difficulty::Normal,
+        }
+    }
+
+    fn table(entries: &[(u32, u64)]) -> HighScores {
+        HighScores {
+            scores: entries
+                .iter()
+                .map(|(score, time)| entry(*score, *time))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn export_round_trips() {
+        let mut high_scores = table(&[(300, 3), (200, 2), (100, 0)]);
+        high_scores.scores[1].difficulty = Difficulty::Hard;
+        let path = std::env::temp_dir().join(format!("high_scores_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        high_scores.export(path).unwrap();
+        let opened = HighScores::open(path);
+        let _ = std::fs::remove_file(path);
+        assert_eq!(opened.unwrap(), high_scores);
+    }
+
+    #[test]
+    fn merge_keeps_the_top_ten_once() {
+        let mut high_scores = table(&[(500, 5), (300, 3), (100, 0)]);
+        let other = (1..=10)
+            .map(|i| (i * 50, i as u64))
+            .chain([(500, 5), (100, 0)]);
+        high_scores.merge(table(&other.collect::<Vec<_>>()));
+        let scores: Vec<(u32, u64)> = high_scores
+            .scores
+            .iter()
+            .map(|entry| (entry.score, entry.time))
+            .collect();
+        // The same recorded score from both tables once, ties by time.
+        assert_eq!(
+            scores,
+            [
+                (500, 5),
+                (500, 10),
+                (450, 9),
+                (400, 8),
+                (350, 7),
+                (300, 3),
+                (300, 6),
+                (250, 5),
+                (200, 4),
+                (150, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_keeps_legacy_scores_of_unknown_time() {
+        let mut high_scores = table(&[(100, 0), (100, 0)]);
+        high_scores.merge(table(&[(100, 0), (100, 7), (100, 7)]));
+        let times: Vec<u64> = high_scores.scores.iter().map(|entry| entry.time).collect();
+        assert_eq!(times, [0, 0, 0, 7]);
+    }
+
+    #[test]
+    fn merge_tells_difficulties_apart() {
+        let mut high_scores = table(&[(100, 7)]);
+        let mut other = table(&[(100, 7), (100, 7), (100, 7)]);
+        other.scores[0].difficulty = Difficulty::Hard;
+        other.scores[2].difficulty = Difficulty::Hard;
+        high_scores.merge(other);
+        let difficulties: Vec<Difficulty> = high_scores
+            .scores
+            .iter()
+            .map(|entry| entry.difficulty)
+            .collect();
+        assert_eq!(difficulties, [Difficulty::Normal, Difficulty::Hard]);
+    }
+
+    #[test]
+    fn read_names_the_bad_field() {
+        let error = HighScores::read("000100 soon normal\n".to_string());
+        assert!(matches!(error, Err(Error::Time(_))));
     }
 }
@@ -1,31 +1,64 @@
-use bevy::{app::AppExit, prelude::*};
+use bevy::prelude::*;
 
 mod assets;
+mod attract;
+mod audio;
+mod autopilot;
 mod camera;
+mod cli;
+mod difficulty;
 mod enemy;
 mod explosion;
 mod game_over;
+#[cfg(test)]
+mod harness;
 mod high_scores;
 mod map;
 mod menu;
 mod minimap;
+mod music;
+mod navigation;
+mod new_game;
 mod person;
 mod player;
 mod projectile;
+mod replay;
+mod rng;
 mod score;
+mod settings;
+mod snapshot;
 mod style;
 mod utils;
+mod wave_intro;
 
 fn main() {
+    let args = cli::Args::parse().unwrap_or_else(|error| exit_with(error));
+    match args.run_headless() {
+        Ok(true) => return,
+        Ok(false) => {}
+        Err(error) => exit_with(error),
+    }
+    if args.patterns {
+        projectile::pattern::scene();
+        return;
+    }
+    let replay = args.replay.as_ref().map(|path| {
+        replay::Tape::load(path)
+            .unwrap_or_else(|error| exit_with(format!("replay {}: {}", path, error)))
+    });
+    let mut settings = settings::Settings::load();
+    if let Some(tape) = &replay {
+        tape.apply(&mut settings);
+    }
+    let msaa = settings.msaa();
     App::new()
         .add_event::<minimap::Ready>()
-        .add_event::<explosion::At>()
         .insert_resource(bevy::asset::AssetMetaCheck::Never)
         .add_plugins((
             DefaultPlugins.set(WindowPlugin {
                 primary_window: Some(Window {
                     title: "Defender".into(),
-                    mode: bevy::window::WindowMode::BorderlessFullscreen,
+                    mode: settings.window_mode(),
                     fit_canvas_to_parent: true,
                     prevent_default_event_handling: false,
                     ..default()
@@ -33,7 +66,17 @@ fn main() {
                 ..default()
             }),
             utils::bevy::Plug,
+            audio::Plug,
+            music::Plug,
+            navigation::Plug,
+            autopilot::Plug,
+            attract::Plug,
+            difficulty::Plug,
+        ))
+        .add_plugins((
             menu::Plug,
+            new_game::Plug,
+            wave_intro::Plug,
             map::Plug,
             player::Plug,
             enemy::Plug,
@@ -41,17 +84,33 @@ fn main() {
             projectile::Plug,
             explosion::Plug,
             game_over::Plug,
+            settings::Plug { settings },
             high_scores::Plug,
+            snapshot::Plug,
+            rng::Plug { seed: args.seed },
+            replay::Plug {
+                record: args.record,
+                play: replay,
+            },
+        ))
+        .insert_resource(Time::<Fixed>::from_hz(
+            args.tick_rate.unwrap_or(utils::bevy::fixed::TICK_RATE),
         ))
         .insert_resource(ClearColor(Color::BLACK))
-        .insert_resource(Msaa::Sample4)
+        .insert_resource(msaa)
+        .init_resource::<camera::Shake>()
         .add_systems(Startup, (assets::load, camera::spawn))
-        .add_systems(Update, (try_exiting, camera::window_height_center))
+        .add_systems(Update, camera::window_height_center)
+        .add_systems(
+            Update,
+            camera::shake
+                .after(camera::window_height_center)
+                .after(minimap::redraw),
+        )
         .run();
 }
 
-fn try_exiting(mut exit: EventWriter<AppExit>, key: Res<Input<KeyCode>>) {
-    if key.pressed(KeyCode::Q) {
-        exit.send(AppExit)
-    }
+fn exit_with(error: String) -> ! {
+    eprintln!("{}", error);
+    std::process::exit(1)
 }
@@ -1,6 +1,9 @@
 use crate::{
     style,
-    utils::{self, bevy::window},
+    utils::{
+        self,
+        bevy::{fixed::Step, window},
+    },
 };
 use bevy::prelude::*;
 
@@ -14,22 +17,30 @@ impl Plugin for Plug {
     fn build(&self, app: &mut App) {
         app.add_plugins(terrain::Plug)
             .insert_resource(MapScroll::new(0.0))
-            .add_systems(Update, scroll)
-            .add_systems(PostUpdate, confine);
+            .add_systems(
+                FixedUpdate,
+                (scroll.in_set(Step::Main), confine.in_set(Step::Late)),
+            );
     }
 }
 
 #[derive(Component)]
 pub struct Confine;
 
-fn confine(mut query: Query<&mut Transform, With<Confine>>, window_size: Res<window::Size>) {
+/// Lowest and highest height ships fly at in a window of `window_size`,
+/// below the minimap.
+pub fn band(window_size: Vec2) -> (f32, f32) {
     let offset = style::BORDER_CONFINEMENT_OFFSET;
+    (
+        offset,
+        window_size.y * (1.0 - style::MINIMAP_SIZE.y) - offset,
+    )
+}
+
+fn confine(mut query: Query<&mut Transform, With<Confine>>, window_size: Res<window::Size>) {
+    let (bottom, top) = band(window_size.0);
     for mut transform in query.iter_mut() {
-        let position = transform.translation;
-        transform.translation.y = position.y.clamp(
-            offset,
-            window_size.0.y * (1.0 - style::MINIMAP_SIZE.y) - offset,
-        );
+        transform.translation.y = transform.translation.y.clamp(bottom, top);
     }
 }
 
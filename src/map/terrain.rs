@@ -10,19 +10,24 @@ pub const POINTS: usize = 1000;
 pub const SEGMENTS: usize = POINTS - 1;
 pub const SEGMENT_LENGTH: f32 = 10.0;
 pub const SEGMENT_HEIGHT: f32 = 400.0;
+/// Noise seed of the terrain every run starts on.
+pub const SEED: u32 = 2137;
 
 pub struct Plug;
 
 impl Plugin for Plug {
     fn build(&self, app: &mut App) {
-        app.insert_resource(gen_terrain())
-            .add_systems(Update, draw)
-            .add_systems(PostUpdate, try_drawing_on_minimap);
+        app.insert_resource(Terrain::new(SEED));
+        if !utils::bevy::headless(app) {
+            app.add_systems(Update, draw)
+                .add_systems(PostUpdate, try_drawing_on_minimap);
+        }
     }
 }
 
 #[derive(Resource)]
 pub struct Terrain {
+    pub seed: u32,
     pub heights: Vec<f32>,
 }
 
@@ -45,8 +50,13 @@ fn try_drawing_on_minimap(
     }
 }
 
-fn gen_terrain() -> Terrain {
-    let seed = 2137;
+impl Terrain {
+    pub fn new(seed: u32) -> Self {
+        gen_terrain(seed)
+    }
+}
+
+fn gen_terrain(seed: u32) -> Terrain {
     let perlin = Perlin::new(seed);
     let mut heights = vec![];
     for i in 0..SEGMENTS {
@@ -61,7 +71,7 @@ fn gen_terrain() -> Terrain {
         heights.push((t * t * t + 0.2) * SEGMENT_HEIGHT);
     }
     heights.push(heights[0]);
-    Terrain { heights }
+    Terrain { seed, heights }
 }
 
 fn draw(
@@ -1,86 +1,187 @@
-use crate::{assets::GameAssets, enemy, person, player, score, style, utils};
-use bevy::{app::AppExit, prelude::*};
-use utils::bevy::{projectile::Projectile, state::Simulation};
+use crate::{
+    attract, navigation, new_game::NewGame, player, rng, settings::SettingsMenu, snapshot, style,
+    utils,
+};
+use bevy::{app::AppExit, prelude::*, ui::FocusPolicy};
+use player::{
+    input::{Action, Bind, Bindings, Devices},
+    rebind::{self, Rebinding},
+};
+use utils::bevy::state::GameState;
 
 #[derive(Component)]
 pub struct PausedMenu;
 
+#[derive(Component)]
+struct BindingRow(Action);
+
+#[derive(Component)]
+struct BindingTiles(Action);
+
+#[derive(Component)]
+struct RebindText;
+
+#[derive(Component)]
+struct ConfirmDialog;
+
+#[derive(Component)]
+struct ConfirmText;
+
 #[derive(Component)]
 pub enum Button {
     Play,
+    Continue,
+    Resume,
+    Restart,
+    Settings,
+    QuitToTitle,
     Exit,
+    Yes,
+    No,
 }
 
 impl Button {
     fn color(&self) -> Color {
         match self {
-            Button::Play => style::MENU_PLAY_COLOR,
-            Button::Exit => style::MENU_EXIT_COLOR,
+            Button::Play | Button::Continue | Button::Resume | Button::Yes => {
+                style::MENU_PLAY_COLOR
+            }
+            Button::Settings | Button::No => style::MENU_OPTION_COLOR,
+            Button::Restart | Button::QuitToTitle | Button::Exit => style::MENU_EXIT_COLOR,
+        }
+    }
+
+    /// Whether it starts or goes back to a run, which can't happen while an
+    /// input is being rebound.
+    fn starts_run(&self) -> bool {
+        matches!(self, Button::Play | Button::Continue | Button::Resume)
+    }
+
+    /// Whether shown in `state`, `saved` if there's a run to continue.
+    fn shown_in(&self, state: GameState, saved: bool) -> bool {
+        match self {
+            Button::Play | Button::Exit => state == GameState::Title,
+            Button::Continue => state == GameState::Title && saved,
+            Button::Resume | Button::Restart | Button::QuitToTitle => state == GameState::Paused,
+            _ => true,
         }
     }
 }
 
+/// Something that throws away the run or the game, done once confirmed.
+#[derive(Clone, Copy)]
+enum Choice {
+    Restart,
+    QuitToTitle,
+    Exit,
+}
+
+impl Choice {
+    fn question(&self) -> &'static str {
+        match self {
+            Choice::Restart => "restart the run?",
+            Choice::QuitToTitle => "quit to title?\nthe run will be saved",
+            Choice::Exit => "quit the game?",
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct Confirm {
+    choice: Option<Choice>,
+}
+
 pub struct Plug;
 
 impl Plugin for Plug {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn)
-            .add_systems(Update, (toggle_visibility, button_change));
+        app.init_resource::<Confirm>()
+            .add_systems(Startup, (spawn, spawn_dialog))
+            .add_systems(
+                Update,
+                (
+                    button_change,
+                    binding_row_press,
+                    update_binding_tiles,
+                    update_rebind_text,
+                    disable_while_rebinding,
+                    ask_to_quit.run_if(rebind::idle).run_if(attract::live),
+                    update_dialog,
+                ),
+            );
+        for state in [GameState::Title, GameState::Paused] {
+            app.add_systems(OnEnter(state), show)
+                .add_systems(OnExit(state), hide);
+        }
     }
 }
 
 const ALPHA: f32 = 1.0 - 1.0 / 16.0;
+/// Of buttons that can't be pressed right now.
+const DISABLED_ALPHA: f32 = 0.15;
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn button_change(
     mut interaction_query: Query<
         (
             &Interaction,
             &mut BackgroundColor,
             &mut BorderColor,
-            &Children,
             &Button,
         ),
         (Changed<Interaction>, With<Button>),
     >,
-    mut text_query: Query<&mut Text>,
     mut commands: Commands,
     mut exit: EventWriter<AppExit>,
-    player_query: Query<With<player::Player>>,
-    enemy_query: Query<Entity, With<enemy::Enemy>>,
-    person_query: Query<Entity, With<person::CharacterState>>,
-    projectile_query: Query<Entity, With<Projectile>>,
-    mut enemies_count: ResMut<enemy::EnemiesCount>,
-    assets: Res<GameAssets>,
-    camera_query: Query<&Transform, With<Camera>>,
-    mut score: ResMut<score::Score>,
+    mut new_game: EventWriter<NewGame>,
+    mut save: EventWriter<snapshot::Save>,
+    mut resume: EventWriter<snapshot::Resume>,
+    state: Res<State<GameState>>,
+    rng: Res<rng::GameRng>,
+    mut menu_query: Query<&mut Style, (With<PausedMenu>, Without<SettingsMenu>)>,
+    mut settings_query: Query<&mut Style, With<SettingsMenu>>,
+    mut confirm: ResMut<Confirm>,
+    rebinding: Res<Rebinding>,
 ) {
-    let mut play = || {
-        let state = Simulation::Running;
-        commands.insert_resource(NextState(Some(state)));
-        if let Err(_) = player_query.get_single() {
-            player::spawn(&mut commands, &assets, &camera_query);
-            for entity in enemy_query.iter() {
-                commands.entity(entity).despawn();
-            }
-            for entity in projectile_query.iter() {
-                commands.entity(entity).despawn();
-            }
-            for entity in person_query.iter() {
-                commands.entity(entity).despawn();
-            }
-            enemies_count.count = 0;
-            enemies_count.wave = 0;
-            score.value = 0;
-        }
+    let mut new_game = || {
+        new_game.send(NewGame {
+            seed: rng.next_seed(),
+        })
     };
-    for (interaction, mut color, mut border_color, children, button) in &mut interaction_query {
-        let mut _text = text_query.get_mut(children[0]).unwrap();
+    for (interaction, mut color, mut border_color, button) in &mut interaction_query {
+        if button.starts_run() && rebinding.action.is_some() {
+            continue;
+        }
         match *interaction {
             Interaction::Pressed => match button {
-                Button::Play => {
-                    play();
+                Button::Play => new_game(),
+                Button::Continue => resume.send(snapshot::Resume),
+                Button::Resume => {
+                    commands.insert_resource(NextState(Some(GameState::Playing)));
+                }
+                Button::Restart => confirm.choice = Some(Choice::Restart),
+                Button::QuitToTitle => confirm.choice = Some(Choice::QuitToTitle),
+                Button::Exit => confirm.choice = Some(Choice::Exit),
+                Button::Yes => {
+                    let choice = confirm.choice.take();
+                    let quitting = matches!(choice, Some(Choice::QuitToTitle | Choice::Exit));
+                    if quitting && *state.get() == GameState::Paused {
+                        save.send(snapshot::Save);
+                    }
+                    match choice {
+                        Some(Choice::Restart) => new_game(),
+                        Some(Choice::QuitToTitle) => {
+                            commands.insert_resource(NextState(Some(GameState::Title)));
+                        }
+                        Some(Choice::Exit) => exit.send(AppExit),
+                        None => {}
+                    }
+                }
+                Button::No => confirm.choice = None,
+                Button::Settings => {
+                    menu_query.single_mut().display = Display::None;
+                    settings_query.single_mut().display = Display::Flex;
                 }
-                Button::Exit => exit.send(AppExit),
             },
             Interaction::Hovered => {
                 color.0 = button.color().with_a(0.75);
@@ -117,24 +218,26 @@ fn spawn(mut commands: Commands, asset_server: Res<AssetServer>) {
             PausedMenu,
         ))
         .with_children(|parent| {
-            let mut show_binding = |tiles: Vec<u32>, msg| {
+            for action in Action::ALL {
                 parent
-                    .spawn(NodeBundle {
-                        style: Style {
-                            justify_content: JustifyContent::SpaceBetween,
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                justify_content: JustifyContent::SpaceBetween,
+                                ..default()
+                            },
+                            background_color: utils::bevy::grey(1.0 / 16.0, ALPHA).into(),
                             ..default()
                         },
-                        background_color: utils::bevy::grey(1.0 / 16.0, ALPHA).into(),
-                        ..default()
-                    })
+                        BindingRow(action),
+                    ))
                     .with_children(|parent| {
                         let section = TextSection::new(
-                            msg,
+                            action.label(),
                             TextStyle {
                                 color: Color::WHITE,
                                 font_size: 24.0,
                                 font: asset_server.load(style::FONT),
-                                ..default()
                             },
                         );
                         parent.spawn(TextBundle {
@@ -147,88 +250,310 @@ fn spawn(mut commands: Commands, asset_server: Res<AssetServer>) {
                             ..default()
                         });
 
-                        parent
-                            .spawn(NodeBundle {
+                        parent.spawn((
+                            NodeBundle {
                                 style: Style {
-                                    width: Val::Px(16.0 * 8.0),
+                                    min_width: Val::Px(16.0 * 8.0),
                                     justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    column_gap: Val::Px(8.0),
                                     ..default()
                                 },
                                 background_color: utils::bevy::grey(1.0, 0.5).into(),
                                 ..default()
-                            })
-                            .with_children(|parent| {
-                                for tile in tiles.iter() {
-                                    let path = format!("ui/tile_{:04}.png", tile);
-                                    parent.spawn((
-                                        NodeBundle {
-                                            style: Style {
-                                                width: Val::Px(16.0 * 3.0),
-                                                height: Val::Px(16.0 * 3.0),
-                                                ..default()
-                                            },
-                                            background_color: Color::WHITE.into(),
-                                            ..default()
-                                        },
-                                        UiImage::new(asset_server.load(path)),
-                                    ));
-                                }
-                            });
+                            },
+                            BindingTiles(action),
+                        ));
                     });
-            };
-            let esc = vec![17];
-            let w = vec![86];
-            let a = vec![120];
-            let s = vec![121];
-            let d = vec![122];
-            let space = vec![235, 236, 237];
-            let control = vec![221, 222];
-            show_binding(esc, "pause");
-            show_binding(a, "move left");
-            show_binding(d, "move right");
-            show_binding(w, "move up");
-            show_binding(s, "move down");
-            show_binding(space, "shoot laser");
-            show_binding(control, "rescue");
-            let mut button = |msg, button: Button| {
-                parent
-                    .spawn((
-                        ButtonBundle {
+            }
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load(style::FONT),
+                        font_size: 24.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                RebindText,
+            ));
+            let font = asset_server.load(style::FONT);
+            spawn_button(parent, &font, "play", Button::Play);
+            spawn_button(parent, &font, "continue", Button::Continue);
+            spawn_button(parent, &font, "resume", Button::Resume);
+            spawn_button(parent, &font, "restart run", Button::Restart);
+            spawn_button(parent, &font, "settings", Button::Settings);
+            spawn_button(parent, &font, "quit to title", Button::QuitToTitle);
+            spawn_button(parent, &font, "exit", Button::Exit);
+        });
+}
+
+fn spawn_button(parent: &mut ChildBuilder, font: &Handle<Font>, msg: &str, button: Button) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    border: UiRect::all(Val::Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::all(Val::Px(5.0)),
+                    ..default()
+                },
+                background_color: button.color().into(),
+                ..default()
+            },
+            button,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                msg,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 40.0,
+                    color: utils::bevy::grey(1.0, 1.0),
+                },
+            ));
+        });
+}
+
+fn spawn_dialog(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load(style::FONT);
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    display: Display::None,
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: utils::bevy::grey(0.0, 0.5).into(),
+                focus_policy: FocusPolicy::Block,
+                z_index: ZIndex::Global(1),
+                ..default()
+            },
+            ConfirmDialog,
+            navigation::Modal,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(16.0),
+                        padding: UiRect::all(Val::Px(24.0)),
+                        border: UiRect::all(Val::Px(1.0)),
+                        ..default()
+                    },
+                    background_color: utils::bevy::grey(1.0 / 16.0, ALPHA).into(),
+                    border_color: utils::bevy::grey(1.0, 0.5).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(
+                            "",
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 32.0,
+                                color: Color::WHITE,
+                            },
+                        )
+                        .with_text_alignment(TextAlignment::Center),
+                        ConfirmText,
+                    ));
+                    parent
+                        .spawn(NodeBundle {
                             style: Style {
-                                border: UiRect::all(Val::Px(10.0)),
-                                justify_content: JustifyContent::Center,
-                                align_items: AlignItems::Center,
-                                padding: UiRect::all(Val::Px(5.0)),
+                                column_gap: Val::Px(16.0),
                                 ..default()
                             },
-                            background_color: button.color().into(),
                             ..default()
-                        },
-                        button,
-                    ))
-                    .with_children(|parent| {
+                        })
+                        .with_children(|parent| {
+                            spawn_button(parent, &font, "yes", Button::Yes);
+                            spawn_button(parent, &font, "no", Button::No);
+                        });
+                });
+        });
+}
+
+fn show(
+    state: Res<State<GameState>>,
+    mut query: Query<&mut Style, With<PausedMenu>>,
+    mut button_query: Query<(&mut Style, &Button), Without<PausedMenu>>,
+    saved: Res<snapshot::Saved>,
+) {
+    query.single_mut().display = Display::Flex;
+    for (mut style, button) in button_query.iter_mut() {
+        style.display = match button.shown_in(*state.get(), saved.run.is_some()) {
+            true => Display::Flex,
+            false => Display::None,
+        };
+    }
+}
+
+fn hide(mut query: Query<&mut Style, With<PausedMenu>>, mut confirm: ResMut<Confirm>) {
+    query.single_mut().display = Display::None;
+    confirm.choice = None;
+}
+
+/// The quit binding asks before quitting, from the menus or pausing the run
+/// first. Ignored between runs and waves, which can't be paused.
+fn ask_to_quit(
+    devices: Devices,
+    bindings: Res<Bindings>,
+    state: Res<State<GameState>>,
+    mut commands: Commands,
+    mut confirm: ResMut<Confirm>,
+) {
+    let quit = bindings.get(Action::Quit);
+    if !quit.iter().any(|bind| devices.just_pressed(bind)) {
+        return;
+    }
+    match state.get() {
+        GameState::Title | GameState::Paused => {}
+        GameState::Playing => {
+            commands.insert_resource(NextState(Some(GameState::Paused)));
+        }
+        _ => return,
+    }
+    confirm.choice = Some(Choice::Exit);
+}
+
+fn update_dialog(
+    confirm: Res<Confirm>,
+    mut dialog_query: Query<&mut Style, With<ConfirmDialog>>,
+    mut text_query: Query<&mut Text, With<ConfirmText>>,
+) {
+    if !confirm.is_changed() {
+        return;
+    }
+    let mut style = dialog_query.single_mut();
+    match confirm.choice {
+        Some(choice) => {
+            style.display = Display::Flex;
+            text_query.single_mut().sections[0].value = choice.question().to_string();
+        }
+        None => style.display = Display::None,
+    }
+}
+
+fn binding_row_press(
+    query: Query<(&Interaction, &BindingRow), Changed<Interaction>>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    for (interaction, row) in query.iter() {
+        if let Interaction::Pressed = interaction {
+            rebinding.start(row.0);
+        }
+    }
+}
+
+fn key_tiles(key: KeyCode) -> Option<Vec<u32>> {
+    use KeyCode::*;
+    let rows: [(&[KeyCode], u32); 5] = [
+        (&[F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12], 18),
+        (
+            &[Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Key0],
+            51,
+        ),
+        (&[Q, W, E, R, T, Y, U, I, O, P], 85),
+        (&[A, S, D, F, G, H, J, K, L], 120),
+        (&[Z, X, C, V, B, N, M], 155),
+    ];
+    for (keys, first) in rows {
+        if let Some(i) = keys.iter().position(|k| *k == key) {
+            return Some(vec![first + i as u32]);
+        }
+    }
+    match key {
+        Escape => Some(vec![17]),
+        Return => Some(vec![134, 135]),
+        ControlLeft | ControlRight => Some(vec![221, 222]),
+        Space => Some(vec![235, 236, 237]),
+        _ => None,
+    }
+}
+
+fn update_binding_tiles(
+    bindings: Res<Bindings>,
+    query: Query<(Entity, &BindingTiles)>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    if !bindings.is_changed() {
+        return;
+    }
+    for (entity, tiles) in query.iter() {
+        commands
+            .entity(entity)
+            .despawn_descendants()
+            .with_children(|parent| {
+                for bind in bindings.get(tiles.0) {
+                    let tiles = match bind {
+                        Bind::Key(key) => key_tiles(*key),
+                        _ => None,
+                    };
+                    let Some(tiles) = tiles else {
                         parent.spawn(TextBundle::from_section(
-                            msg,
+                            bind.name(),
                             TextStyle {
                                 font: asset_server.load(style::FONT),
-                                font_size: 40.0,
-                                color: utils::bevy::grey(1.0, 1.0),
+                                font_size: 16.0,
+                                color: Color::BLACK,
                             },
                         ));
+                        continue;
+                    };
+                    parent.spawn(NodeBundle::default()).with_children(|parent| {
+                        for tile in tiles.iter() {
+                            let path = format!("ui/tile_{:04}.png", tile);
+                            parent.spawn((
+                                NodeBundle {
+                                    style: Style {
+                                        width: Val::Px(16.0 * 3.0),
+                                        height: Val::Px(16.0 * 3.0),
+                                        ..default()
+                                    },
+                                    background_color: Color::WHITE.into(),
+                                    ..default()
+                                },
+                                UiImage::new(asset_server.load(path)),
+                            ));
+                        }
                     });
-            };
-            button("play", Button::Play);
-            button("exit", Button::Exit);
-        });
+                }
+            });
+    }
 }
 
-fn toggle_visibility(
-    state: Res<State<Simulation>>,
-    mut query: Query<&mut Style, With<PausedMenu>>,
+fn disable_while_rebinding(
+    rebinding: Res<Rebinding>,
+    mut query: Query<(&mut BackgroundColor, &Button)>,
 ) {
-    let mut style = query.get_single_mut().unwrap();
-    match state.get() {
-        Simulation::Paused => style.display = Display::Flex,
-        Simulation::Running => style.display = Display::None,
+    if !rebinding.is_changed() {
+        return;
+    }
+    for (mut color, button) in query.iter_mut() {
+        if button.starts_run() {
+            color.0 = button.color().with_a(match rebinding.action {
+                Some(_) => DISABLED_ALPHA,
+                None => 0.5,
+            });
+        }
     }
 }
+
+fn update_rebind_text(rebinding: Res<Rebinding>, mut query: Query<&mut Text, With<RebindText>>) {
+    let mut text = query.single_mut();
+    text.sections[0].value = if rebinding.action.is_some() {
+        rebinding.message.clone()
+    } else {
+        "click an action to rebind".to_string()
+    };
+}
@@ -58,7 +58,7 @@ pub fn redraw(
     let m = Ready::new(window_size.0, camera_position);
     let f = m.f();
     '_border: {
-        let points = vec![Vec2::ZERO, Vec2::Y, Vec2::ONE, Vec2::X];
+        let points = [Vec2::ZERO, Vec2::Y, Vec2::ONE, Vec2::X];
         gizmos.linestrip(points.iter().map(&f), style::MINIMAP_COLOR);
         gizmos.line(
             f(&Vec2::new(-1.0, 0.0)),
@@ -71,7 +71,7 @@ pub fn redraw(
         let half_screen_x = 0.5 * window_size.0.x / map::SIZE;
         let min_x = 0.5 - half_screen_x;
         let max_x = 0.5 + half_screen_x;
-        let mut points = vec![
+        let mut points = [
             Vec2::new(min_x, HEIGHT),
             Vec2::new(min_x, 0.0),
             Vec2::new(max_x, 0.0),
@@ -0,0 +1,255 @@
+use crate::{
+    audio::{self, Bus},
+    enemy::{self, Enemy},
+    settings::Settings,
+    style, utils,
+};
+use bevy::prelude::*;
+use utils::bevy::{state::GameState, window};
+
+/// Seconds for one theme to fully replace another.
+const CROSSFADE_TIME: f32 = 2.0;
+/// Seconds for the intense layer to follow the action.
+const INTENSITY_TIME: f32 = 1.0;
+/// How long music stays ducked after a voice line starts.
+const DUCK_TIME: f32 = 2.5;
+/// Music level while ducked.
+const DUCK_GAIN: f32 = 0.3;
+/// Enemies on screen for full intensity.
+const MANY_ENEMIES: f32 = 6.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Theme {
+    Title,
+    Gameplay,
+}
+
+impl Theme {
+    pub fn for_state(state: GameState) -> Self {
+        match state {
+            GameState::Title | GameState::GameOver | GameState::HighScoreEntry => Theme::Title,
+            _ => Theme::Gameplay,
+        }
+    }
+}
+
+/// A looped track, the intense one is layered over the gameplay theme.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Track {
+    Title,
+    Gameplay,
+    Intense,
+}
+
+impl Track {
+    const ALL: [Track; 3] = [Track::Title, Track::Gameplay, Track::Intense];
+
+    fn path(&self) -> &'static str {
+        match self {
+            Track::Title => style::TITLE_MUSIC,
+            Track::Gameplay => style::GAMEPLAY_MUSIC,
+            Track::Intense => style::INTENSE_MUSIC,
+        }
+    }
+
+    /// Whether its file is there, the web build can't tell and assumes so.
+    fn available(&self) -> bool {
+        #[cfg(not(target_family = "wasm"))]
+        return std::path::Path::new("assets").join(self.path()).exists();
+        #[cfg(target_family = "wasm")]
+        true
+    }
+}
+
+/// How action on screen translates to intensity, from 0 to 1.
+pub fn intensity(visible_enemies: usize, abduction: bool) -> f32 {
+    match abduction {
+        true => 1.0,
+        false => (visible_enemies as f32 / MANY_ENEMIES).min(1.0),
+    }
+}
+
+/// Levels of every track, moved towards what the game asks for a little
+/// every frame. Knows nothing about audio devices.
+#[derive(Resource)]
+pub struct Mix {
+    pub theme: Theme,
+    pub intensity: f32,
+    levels: [f32; 3],
+    duck: f32,
+    duck_left: f32,
+}
+
+impl Default for Mix {
+    fn default() -> Self {
+        Self {
+            theme: Theme::Title,
+            intensity: 0.0,
+            levels: [0.0; 3],
+            duck: 1.0,
+            duck_left: 0.0,
+        }
+    }
+}
+
+impl Mix {
+    fn target(&self, track: Track) -> f32 {
+        match (self.theme, track) {
+            (Theme::Title, Track::Title) => 1.0,
+            (Theme::Gameplay, Track::Gameplay) => 1.0,
+            (Theme::Gameplay, Track::Intense) => self.intensity,
+            _ => 0.0,
+        }
+    }
+
+    pub fn duck(&mut self) {
+        self.duck_left = DUCK_TIME;
+    }
+
+    pub fn step(&mut self, delta: f32) {
+        for (i, track) in Track::ALL.into_iter().enumerate() {
+            let rate = match track {
+                Track::Intense if self.theme == Theme::Gameplay => INTENSITY_TIME,
+                _ => CROSSFADE_TIME,
+            };
+            self.levels[i] = utils::range::Range {
+                start: self.levels[i],
+                end: self.target(track),
+            }
+            .step(delta / rate);
+        }
+        self.duck_left = (self.duck_left - delta).max(0.0);
+        self.duck = utils::range::Range {
+            start: self.duck,
+            end: if self.duck_left > 0.0 { DUCK_GAIN } else { 1.0 },
+        }
+        .step(delta * 4.0);
+    }
+
+    pub fn level(&self, track: Track) -> f32 {
+        let i = Track::ALL.iter().position(|t| *t == track).unwrap();
+        self.levels[i] * self.duck
+    }
+}
+
+pub struct Plug;
+
+impl Plugin for Plug {
+    fn build(&self, app: &mut App) {
+        if let Some(track) = Track::ALL.into_iter().find(|track| !track.available()) {
+            info!("no music, {} is missing", track.path());
+            return;
+        }
+        app.init_resource::<Mix>()
+            .add_systems(Startup, spawn_tracks)
+            .add_systems(Update, (start_tracks, update_mix, set_volumes).chain());
+    }
+}
+
+/// Starts paused, so they can all be started at once.
+fn spawn_tracks(mut commands: Commands, asset_server: Res<AssetServer>) {
+    for track in Track::ALL {
+        commands.spawn((
+            AudioBundle {
+                source: asset_server.load(track.path()),
+                settings: PlaybackSettings::LOOP
+                    .with_volume(utils::bevy::volume(0.0))
+                    .paused(),
+            },
+            track,
+        ));
+    }
+}
+
+/// Plays every track from the same moment once all of them are loaded, so
+/// the layers stay in step.
+fn start_tracks(query: Query<&AudioSink, With<Track>>, mut started: Local<bool>) {
+    if *started || query.iter().count() < Track::ALL.len() {
+        return;
+    }
+    for sink in query.iter() {
+        sink.play();
+    }
+    *started = true;
+}
+
+fn update_mix(
+    mut mix: ResMut<Mix>,
+    state: Res<State<GameState>>,
+    mut sounds: EventReader<audio::Play>,
+    enemy_query: Query<(&Transform, &Enemy)>,
+    camera_query: Query<&Transform, With<Camera>>,
+    window_size: Res<window::Size>,
+    time: Res<Time<Real>>,
+) {
+    mix.theme = Theme::for_state(*state.get());
+    if sounds.read().any(|play| play.sound.bus() == Bus::Voice) {
+        mix.duck();
+    }
+    let camera_x = camera_query.single().translation.x;
+    let visible = enemy_query
+        .iter()
+        .filter(|(transform, _)| enemy::visible(transform.translation.x, camera_x, window_size.0.x))
+        .count();
+    let abduction = enemy_query.iter().any(|(_, enemy)| enemy.person.is_some());
+    mix.intensity = intensity(visible, abduction);
+    mix.step(time.delta_seconds());
+}
+
+fn set_volumes(mix: Res<Mix>, settings: Res<Settings>, query: Query<(&AudioSink, &Track)>) {
+    for (sink, track) in query.iter() {
+        sink.set_volume(mix.level(*track) * settings.bus(Bus::Music));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settle(mix: &mut Mix) {
+        for _ in 0..100 {
+            mix.step(0.1);
+        }
+    }
+
+    #[test]
+    fn crossfades_between_themes() {
+        let mut mix = Mix::default();
+        settle(&mut mix);
+        assert_eq!(mix.level(Track::Title), 1.0);
+        mix.theme = Theme::Gameplay;
+        mix.step(CROSSFADE_TIME * 0.5);
+        assert!(mix.level(Track::Title) > 0.0 && mix.level(Track::Gameplay) > 0.0);
+        settle(&mut mix);
+        assert_eq!(mix.level(Track::Title), 0.0);
+        assert_eq!(mix.level(Track::Gameplay), 1.0);
+    }
+
+    #[test]
+    fn intense_layer_follows_action_in_gameplay_only() {
+        let mut mix = Mix {
+            intensity: intensity(0, true),
+            ..default()
+        };
+        settle(&mut mix);
+        assert_eq!(mix.level(Track::Intense), 0.0);
+        mix.theme = Theme::Gameplay;
+        settle(&mut mix);
+        assert_eq!(mix.level(Track::Intense), 1.0);
+        mix.intensity = intensity(3, false);
+        settle(&mut mix);
+        assert_eq!(mix.level(Track::Intense), 0.5);
+    }
+
+    #[test]
+    fn ducks_during_voice_lines() {
+        let mut mix = Mix::default();
+        settle(&mut mix);
+        mix.duck();
+        mix.step(0.5);
+        assert_eq!(mix.level(Track::Title), DUCK_GAIN);
+        mix.step(DUCK_TIME);
+        settle(&mut mix);
+        assert_eq!(mix.level(Track::Title), 1.0);
+    }
+}
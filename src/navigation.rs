@@ -0,0 +1,198 @@
+use crate::{
+    player::{
+        input::{Action, Bind, Bindings, Devices},
+        rebind,
+    },
+    style,
+};
+use bevy::{prelude::*, ui::FocusPolicy, ui::UiSystem};
+
+/// How much left and right move a slider.
+const NUDGE: f32 = 0.05;
+
+/// Takes the focus away from everything outside it while shown.
+#[derive(Component)]
+pub struct Modal;
+
+/// Moved with left and right instead of being pressed.
+#[derive(Component)]
+pub struct Slider;
+
+/// Asks the slider `entity` to move by `amount`.
+#[derive(Event)]
+pub struct Nudge {
+    pub entity: Entity,
+    pub amount: f32,
+}
+
+/// The button keyboard and gamepad input goes to. Stays empty until one of
+/// them is used, so the mouse doesn't get a stray frame.
+#[derive(Resource, Default)]
+struct Focus {
+    entity: Option<Entity>,
+    /// Pressed on our behalf last frame, released on the next one.
+    pressed: Option<Entity>,
+}
+
+#[derive(Component)]
+struct FocusFrame;
+
+pub struct Plug;
+
+impl Plugin for Plug {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Focus>()
+            .add_event::<Nudge>()
+            .add_systems(Startup, spawn_frame)
+            .add_systems(
+                PreUpdate,
+                navigate.after(UiSystem::Focus).run_if(rebind::idle),
+            )
+            .add_systems(PostUpdate, draw_frame);
+    }
+}
+
+/// Visible buttons in reading order, only those of a shown modal if any.
+fn candidates(
+    button_query: &Query<(Entity, &Node, &GlobalTransform, Option<&Slider>), With<Interaction>>,
+    modal_query: &Query<(Entity, &Node), With<Modal>>,
+    parent_query: &Query<&Parent>,
+) -> Vec<Entity> {
+    let modal = modal_query
+        .iter()
+        .find(|(_, node)| node.size() != Vec2::ZERO)
+        .map(|(entity, _)| entity);
+    let inside = |mut entity: Entity, modal: Entity| loop {
+        if entity == modal {
+            return true;
+        }
+        match parent_query.get(entity) {
+            Ok(parent) => entity = parent.get(),
+            Err(_) => return false,
+        }
+    };
+    let mut buttons: Vec<_> = button_query
+        .iter()
+        .filter(|(_, node, ..)| node.size() != Vec2::ZERO)
+        .filter(|(entity, ..)| modal.is_none_or(|modal| inside(*entity, modal)))
+        .map(|(entity, _, transform, _)| (entity, transform.translation()))
+        .collect();
+    buttons.sort_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+    buttons.into_iter().map(|(entity, _)| entity).collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn navigate(
+    devices: Devices,
+    bindings: Res<Bindings>,
+    mut focus: ResMut<Focus>,
+    button_query: Query<(Entity, &Node, &GlobalTransform, Option<&Slider>), With<Interaction>>,
+    modal_query: Query<(Entity, &Node), With<Modal>>,
+    parent_query: Query<&Parent>,
+    mut interaction_query: Query<&mut Interaction>,
+    mut nudges: EventWriter<Nudge>,
+) {
+    if let Some(entity) = focus.pressed.take() {
+        if let Ok(mut interaction) = interaction_query.get_mut(entity) {
+            if *interaction == Interaction::Pressed {
+                *interaction = Interaction::None;
+            }
+        }
+    }
+    // Clicks reach the buttons on their own.
+    let any = |action: Action, key: KeyCode| {
+        devices.just_pressed(&Bind::Key(key))
+            || bindings
+                .get(action)
+                .iter()
+                .filter(|bind| !bind.is_mouse())
+                .any(|bind| devices.just_pressed(bind))
+    };
+    let step = match (
+        any(Action::MoveUp, KeyCode::Up),
+        any(Action::MoveDown, KeyCode::Down),
+    ) {
+        (true, false) => -1,
+        (false, true) => 1,
+        _ => 0,
+    };
+    let side = match (
+        any(Action::MoveLeft, KeyCode::Left),
+        any(Action::MoveRight, KeyCode::Right),
+    ) {
+        (true, false) => -1.0,
+        (false, true) => 1.0,
+        _ => 0.0,
+    };
+    let select = any(Action::Shoot, KeyCode::Return);
+
+    let buttons = candidates(&button_query, &modal_query, &parent_query);
+    let current = focus
+        .entity
+        .and_then(|entity| buttons.iter().position(|other| *other == entity));
+    let current = match (current, focus.entity.is_some() || step != 0) {
+        (Some(i), _) => i as isize + step,
+        (None, true) => 0,
+        (None, false) => return,
+    };
+    let Some(&entity) = buttons.get(current.rem_euclid(buttons.len().max(1) as isize) as usize)
+    else {
+        focus.entity = None;
+        return;
+    };
+    focus.entity = Some(entity);
+    let slider = matches!(button_query.get(entity), Ok((.., Some(_))));
+    if slider && side != 0.0 {
+        nudges.send(Nudge {
+            entity,
+            amount: side * NUDGE,
+        });
+    }
+    if select && !slider {
+        if let Ok(mut interaction) = interaction_query.get_mut(entity) {
+            *interaction = Interaction::Pressed;
+            focus.pressed = Some(entity);
+        }
+    }
+}
+
+fn spawn_frame(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                border: UiRect::all(Val::Px(3.0)),
+                ..default()
+            },
+            border_color: style::MENU_FOCUS_COLOR.into(),
+            focus_policy: FocusPolicy::Pass,
+            z_index: ZIndex::Global(i32::MAX),
+            ..default()
+        },
+        FocusFrame,
+    ));
+}
+
+fn draw_frame(
+    focus: Res<Focus>,
+    node_query: Query<(&Node, &GlobalTransform), Without<FocusFrame>>,
+    mut frame_query: Query<&mut Style, With<FocusFrame>>,
+) {
+    let mut style = frame_query.single_mut();
+    let focused = focus
+        .entity
+        .and_then(|entity| node_query.get(entity).ok())
+        .filter(|(node, _)| node.size() != Vec2::ZERO);
+    let Some((node, transform)) = focused else {
+        style.display = Display::None;
+        return;
+    };
+    let size = node.size();
+    let corner = transform.translation().truncate() - size * 0.5;
+    style.display = Display::Flex;
+    style.left = Val::Px(corner.x);
+    style.top = Val::Px(corner.y);
+    style.width = Val::Px(size.x);
+    style.height = Val::Px(size.y);
+}
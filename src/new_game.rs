@@ -0,0 +1,118 @@
+use crate::{assets::GameAssets, enemy, person, player, projectile, rng, score, utils};
+use bevy::prelude::*;
+use utils::bevy::{projectile::Projectile, state::GameState};
+
+/// Starts a fresh run, with gameplay randomness seeded by `seed`.
+#[derive(Event)]
+pub struct NewGame {
+    pub seed: u64,
+}
+
+pub struct Plug;
+
+impl Plugin for Plug {
+    fn build(&self, app: &mut App) {
+        app.add_event::<NewGame>().add_systems(PostUpdate, reset);
+    }
+}
+
+/// Clears whatever is left of the last run, spawns the player and goes to
+/// the intro of the first wave.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn reset(
+    mut events: EventReader<NewGame>,
+    mut commands: Commands,
+    enemy_query: Query<Entity, With<enemy::Enemy>>,
+    person_query: Query<Entity, With<person::CharacterState>>,
+    projectile_query: Query<
+        Entity,
+        Or<(
+            With<Projectile>,
+            With<projectile::pattern::Scheduled>,
+            With<player::power_up::Pickup>,
+            With<projectile::beam::Beam>,
+        )>,
+    >,
+    player_query: Query<Entity, With<player::Player>>,
+    mut enemies_count: ResMut<enemy::EnemiesCount>,
+    mut reinforcements: ResMut<enemy::wave::Reinforcements>,
+    assets: Res<GameAssets>,
+    mut camera_query: Query<&mut Transform, With<Camera>>,
+    mut score: ResMut<score::Score>,
+    mut stats: ResMut<score::WaveStats>,
+    mut rng: ResMut<rng::GameRng>,
+) {
+    let Some(new_game) = events.read().last() else {
+        return;
+    };
+    rng.reseed(new_game.seed);
+    let mut camera = camera_query.single_mut();
+    camera.translation.x = 0.0;
+    let leftovers = enemy_query
+        .iter()
+        .chain(person_query.iter())
+        .chain(projectile_query.iter())
+        .chain(player_query.iter());
+    for entity in leftovers {
+        commands.entity(entity).despawn();
+    }
+    player::spawn(&mut commands, &assets, camera.translation);
+    enemies_count.count = 0;
+    enemies_count.wave = 0;
+    reinforcements.pending.clear();
+    score.value = 0;
+    *stats = score::WaveStats::default();
+    commands.insert_resource(NextState(Some(GameState::WaveIntro)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projectile::{self, laser::Laser};
+
+    #[test]
+    fn reset_clears_last_run() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, utils::bevy::state::Plug, Plug))
+            .add_plugins(rng::Plug { seed: None })
+            .init_resource::<GameAssets>()
+            .insert_resource(enemy::EnemiesCount { count: 3, wave: 7 })
+            .init_resource::<enemy::wave::Reinforcements>()
+            .insert_resource(score::Score { value: 1234 })
+            .init_resource::<score::WaveStats>();
+        app.world.spawn(Camera2dBundle {
+            transform: Transform::from_xyz(500.0, 300.0, 0.0),
+            ..default()
+        });
+        app.world.send_event(NewGame { seed: 1 });
+        app.update();
+
+        let assets = GameAssets::default();
+        let lander = enemy::bundle(Vec3::ZERO, None, enemy::lander::Lander, &assets);
+        app.world.spawn(lander);
+        let person = person::bundle(Vec2::ZERO, person::CharacterState::Grounded, &assets);
+        app.world.spawn(person);
+        let laser = projectile::Bundle::new(&assets, Vec3::ZERO, 0.0, 1.0, Color::RED, Laser);
+        app.world.spawn(laser);
+        app.world.resource_mut::<score::Score>().value = 1234;
+        app.world.send_event(NewGame { seed: 42 });
+        app.update();
+        app.update();
+
+        let world = &mut app.world;
+        assert_eq!(world.query::<&enemy::Enemy>().iter(world).count(), 0);
+        let people = world.query::<&person::CharacterState>().iter(world).count();
+        assert_eq!(people, 0);
+        assert_eq!(world.query::<&Projectile>().iter(world).count(), 0);
+        let player = world
+            .query_filtered::<&Transform, With<player::Player>>()
+            .single(world);
+        assert_eq!(player.translation.x, 0.0);
+        assert_eq!(world.resource::<score::Score>().value, 0);
+        let enemies_count = world.resource::<enemy::EnemiesCount>();
+        assert_eq!((enemies_count.count, enemies_count.wave), (0, 0));
+        assert_eq!(world.resource::<rng::GameRng>().seed(), 42);
+        let state = world.resource::<State<GameState>>();
+        assert_eq!(*state.get(), GameState::WaveIntro);
+    }
+}
@@ -1,14 +1,22 @@
 use bevy::prelude::*;
 
 use crate::{
-    assets::{audio, GameAssets},
+    assets::GameAssets,
+    audio::{self, Sound},
     enemy::Enemy,
     map, minimap,
     player::*,
     projectile,
     score::Score,
     style,
-    utils::{bevy::hit::*, bevy::state::Simulation},
+    utils::{
+        self,
+        bevy::{
+            fixed::{Interpolated, Step},
+            hit::*,
+            state::simulating,
+        },
+    },
 };
 
 #[derive(Component)]
@@ -19,7 +27,7 @@ pub struct AnimationTimer {
     timer: Timer,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Copy)]
 pub enum CharacterState {
     CapturedBy(Entity, Vec2),
     Falling,
@@ -34,6 +42,7 @@ pub struct Bundle {
     laser_hit: Hittable<projectile::laser::Laser>,
     player_hit: Hittable<Player>,
     scroll: map::Scroll,
+    interpolated: Interpolated,
 }
 
 pub struct Plug;
@@ -44,12 +53,14 @@ impl Plugin for Plug {
             timer: Timer::from_seconds(0.1, TimerMode::Repeating),
         })
         .add_systems(
-            PostUpdate,
-            (
-                try_drawing_on_minimap,
-                (update, laser_hit, player_hit).run_if(in_state(Simulation::Running)),
-            ),
+            FixedUpdate,
+            (update, laser_hit, player_hit)
+                .run_if(simulating)
+                .in_set(Step::Late),
         );
+        if !utils::bevy::headless(app) {
+            app.add_systems(PostUpdate, try_drawing_on_minimap);
+        }
     }
 }
 
@@ -81,6 +92,7 @@ pub fn bundle(position: Vec2, state: CharacterState, assets: &GameAssets) -> Bun
         laser_hit: Hittable::new(style::PERSON_BOUND),
         player_hit: Hittable::new(style::PERSON_BOUND),
         scroll: map::Scroll,
+        interpolated: Interpolated::default(),
     }
 }
 
@@ -96,7 +108,7 @@ pub fn update(
     timer.timer.tick(time.delta());
     for (mut state, mut transform, mut sprite) in query.iter_mut() {
         if let CharacterState::CapturedBy(entity, _) = *state {
-            if let Err(_) = captor_query.get(entity) {
+            if captor_query.get(entity).is_err() {
                 *state = CharacterState::Falling;
             }
         }
@@ -134,7 +146,7 @@ fn laser_hit(
     mut commands: Commands,
 ) {
     for (person_entity, state, hittable) in query.iter() {
-        if let Some(_) = hittable.hit_entity {
+        if hittable.hit_entity.is_some() {
             commands.entity(person_entity).despawn();
             if let CharacterState::CapturedBy(entity, _) = state {
                 if let Ok(mut enemy) = enemy_query.get_mut(*entity) {
@@ -146,18 +158,17 @@ fn laser_hit(
 }
 
 fn player_hit(
-    mut query: Query<(&Hittable<Player>, &mut CharacterState), With<Person>>,
-    mut commands: Commands,
+    mut query: Query<(&Hittable<Player>, &mut CharacterState, &Transform), With<Person>>,
     controls: Res<input::Controls>,
     mut score: ResMut<Score>,
-    assets: Res<GameAssets>,
+    mut sounds: EventWriter<audio::Play>,
 ) {
-    for (hittable, mut state) in query.iter_mut() {
+    for (hittable, mut state, transform) in query.iter_mut() {
         if let Some(player_entity) = hittable.hit_entity {
             if controls.rescue && !matches!(*state, CharacterState::CapturedBy(_, _)) {
                 score.value += 100;
                 *state = CharacterState::CapturedBy(player_entity, PLAYER_OFFSET);
-                commands.spawn(audio(assets.capture_audio.clone(), style::VOLUME));
+                sounds.send(audio::Play::at(Sound::Capture, transform.translation));
             }
         }
     }
@@ -1,24 +1,162 @@
-use crate::utils;
-use bevy::prelude::*;
-use utils::{bevy::state::Simulation, Side};
+use super::rebind;
+use crate::{attract, replay, settings::Settings, utils};
+use bevy::{
+    ecs::system::SystemParam,
+    input::{gamepad::*, InputSystem},
+    prelude::*,
+    window::PrimaryWindow,
+};
+use serde::{Deserialize, Serialize};
+use utils::{bevy::state::GameState, Side};
 
-#[derive(Clone, Copy)]
-enum Bind {
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Bind {
     Key(KeyCode),
-    _Button(MouseButton),
+    Button(MouseButton),
+    Pad(GamepadButtonType),
 }
 
-#[derive(Resource)]
+impl Bind {
+    pub fn is_mouse(&self) -> bool {
+        matches!(self, Bind::Button(_))
+    }
+
+    /// Keyboard, mouse or gamepad, in the order binds are listed.
+    fn device(&self) -> u8 {
+        match self {
+            Bind::Key(_) => 0,
+            Bind::Button(_) => 1,
+            Bind::Pad(_) => 2,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Bind::Key(key) => format!("{:?}", key),
+            Bind::Button(button) => format!("mouse {:?}", button),
+            Bind::Pad(button) => format!("pad {:?}", button),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    Pause,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    Shoot,
+    Rescue,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: [Action; 8] = [
+        Action::Pause,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::Shoot,
+        Action::Rescue,
+        Action::Quit,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Pause => "pause",
+            Action::MoveLeft => "move left",
+            Action::MoveRight => "move right",
+            Action::MoveUp => "move up",
+            Action::MoveDown => "move down",
+            Action::Shoot => "shoot laser",
+            Action::Rescue => "rescue",
+            Action::Quit => "quit game",
+        }
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize)]
 pub struct Bindings {
-    move_up: Bind,
-    move_down: Bind,
-    move_left: Bind,
-    move_right: Bind,
-    shoot: Bind,
-    rescue: Bind,
+    move_up: Vec<Bind>,
+    move_down: Vec<Bind>,
+    move_left: Vec<Bind>,
+    move_right: Vec<Bind>,
+    shoot: Vec<Bind>,
+    rescue: Vec<Bind>,
+    pause: Vec<Bind>,
+    /// Missing from bindings saved before it could be rebound.
+    #[serde(default = "default_quit")]
+    quit: Vec<Bind>,
+    pub dead_zone: f32,
 }
 
-#[derive(Resource)]
+const PATH: &str = "assets/bindings.json";
+
+impl Bindings {
+    pub fn load() -> Self {
+        #[cfg(not(target_family = "wasm"))]
+        if let Ok(input) = std::fs::read_to_string(PATH) {
+            match serde_json::from_str(&input) {
+                Ok(bindings) => return bindings,
+                Err(error) => warn!("invalid bindings, using defaults: {}", error),
+            }
+        }
+        init_bindings()
+    }
+
+    pub fn save(&self) {
+        #[cfg(not(target_family = "wasm"))]
+        if let Ok(output) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::write(PATH, output);
+        }
+    }
+
+    pub fn get(&self, action: Action) -> &Vec<Bind> {
+        match action {
+            Action::Pause => &self.pause,
+            Action::MoveLeft => &self.move_left,
+            Action::MoveRight => &self.move_right,
+            Action::MoveUp => &self.move_up,
+            Action::MoveDown => &self.move_down,
+            Action::Shoot => &self.shoot,
+            Action::Rescue => &self.rescue,
+            Action::Quit => &self.quit,
+        }
+    }
+
+    fn get_mut(&mut self, action: Action) -> &mut Vec<Bind> {
+        match action {
+            Action::Pause => &mut self.pause,
+            Action::MoveLeft => &mut self.move_left,
+            Action::MoveRight => &mut self.move_right,
+            Action::MoveUp => &mut self.move_up,
+            Action::MoveDown => &mut self.move_down,
+            Action::Shoot => &mut self.shoot,
+            Action::Rescue => &mut self.rescue,
+            Action::Quit => &mut self.quit,
+        }
+    }
+
+    /// Which other action already uses `bind`, if any.
+    pub fn conflict(&self, action: Action, bind: Bind) -> Option<Action> {
+        Action::ALL
+            .into_iter()
+            .filter(|other| *other != action)
+            .find(|other| self.get(*other).contains(&bind))
+    }
+
+    /// Replaces the binds of the same device, keyboard, mouse or gamepad.
+    pub fn rebind(&mut self, action: Action, bind: Bind) {
+        let binds = self.get_mut(action);
+        binds.retain(|old| old.device() != bind.device());
+        binds.push(bind);
+        binds.sort_by_key(|bind| bind.device());
+    }
+}
+
+#[derive(Resource, Clone, Copy, Default)]
 pub struct Controls {
     pub move_up: bool,
     pub move_down: bool,
@@ -26,6 +164,10 @@ pub struct Controls {
     pub move_right: bool,
     pub shoot: bool,
     pub rescue: bool,
+    /// Left stick past the dead zone, each axis in -1..1.
+    pub analog: Vec2,
+    /// Height the ship should approach in mouse control.
+    pub target_y: Option<f32>,
 }
 
 impl Controls {
@@ -34,17 +176,35 @@ impl Controls {
             Some(Side::Left)
         } else if self.move_right {
             Some(Side::Right)
+        } else if self.analog.x < 0.0 {
+            Some(Side::Left)
+        } else if self.analog.x > 0.0 {
+            Some(Side::Right)
         } else {
             None
         }
     }
 
     pub fn thrust(&self) -> bool {
-        self.move_left || self.move_right
+        self.facing().is_some()
+    }
+
+    pub fn horizontal(&self) -> f32 {
+        if self.move_left || self.move_right {
+            1.0
+        } else {
+            self.analog.x.abs()
+        }
     }
 
     pub fn vertical(&self) -> f32 {
-        (if self.move_up { 1.0 } else { 0.0 }) + (if self.move_down { -1.0 } else { 0.0 })
+        let digital =
+            (if self.move_up { 1.0 } else { 0.0 }) + (if self.move_down { -1.0 } else { 0.0 });
+        if digital != 0.0 {
+            digital
+        } else {
+            self.analog.y
+        }
     }
 }
 
@@ -52,51 +212,214 @@ pub struct Plug;
 
 impl Plugin for Plug {
     fn build(&self, app: &mut App) {
-        app.insert_resource(init_bindings())
-            .insert_resource(init_controls())
-            .add_systems(Update, input.run_if(in_state(Simulation::Running)));
+        if utils::bevy::headless(app) {
+            app.insert_resource(init_bindings())
+                .init_resource::<Controls>();
+            return;
+        }
+        app.insert_resource(Bindings::load())
+            .init_resource::<Controls>()
+            .add_systems(PreUpdate, input.after(InputSystem).run_if(replay::live))
+            .add_systems(
+                Update,
+                (
+                    try_pausing
+                        .run_if(replay::live)
+                        .run_if(attract::live)
+                        .run_if(rebind::idle)
+                        .before(rebind::capture),
+                    gamepad_connections.run_if(attract::live),
+                ),
+            );
     }
 }
 
+fn default_quit() -> Vec<Bind> {
+    vec![Bind::Key(KeyCode::Q)]
+}
+
 fn init_bindings() -> Bindings {
     use Bind::*;
+    use GamepadButtonType::*;
     Bindings {
-        move_up: Key(KeyCode::W),
-        move_down: Key(KeyCode::S),
-        move_left: Key(KeyCode::A),
-        move_right: Key(KeyCode::D),
-        shoot: Key(KeyCode::Space),
-        rescue: Key(KeyCode::ControlLeft),
+        move_up: vec![Key(KeyCode::W), Pad(DPadUp)],
+        move_down: vec![Key(KeyCode::S), Pad(DPadDown)],
+        move_left: vec![Key(KeyCode::A), Pad(DPadLeft)],
+        move_right: vec![Key(KeyCode::D), Pad(DPadRight)],
+        shoot: vec![Key(KeyCode::Space), Button(MouseButton::Left), Pad(South)],
+        rescue: vec![
+            Key(KeyCode::ControlLeft),
+            Button(MouseButton::Right),
+            Pad(West),
+        ],
+        pause: vec![Key(KeyCode::Escape), Pad(North), Pad(Start)],
+        quit: default_quit(),
+        dead_zone: 0.2,
     }
 }
 
-fn init_controls() -> Controls {
-    Controls {
-        move_up: false,
-        move_down: false,
-        move_left: false,
-        move_right: false,
-        shoot: false,
-        rescue: false,
+#[derive(SystemParam)]
+pub struct Devices<'w> {
+    key: Res<'w, Input<KeyCode>>,
+    button: Res<'w, Input<MouseButton>>,
+    pad_button: Res<'w, Input<GamepadButton>>,
+    pad_axis: Res<'w, Axis<GamepadAxis>>,
+    gamepads: Res<'w, Gamepads>,
+}
+
+impl Devices<'_> {
+    fn pressed(&self, bind: &Bind) -> bool {
+        match *bind {
+            Bind::Key(v) => self.key.pressed(v),
+            Bind::Button(v) => self.button.pressed(v),
+            Bind::Pad(v) => self
+                .gamepads
+                .iter()
+                .any(|gamepad| self.pad_button.pressed(GamepadButton::new(gamepad, v))),
+        }
+    }
+
+    pub fn just_pressed(&self, bind: &Bind) -> bool {
+        match *bind {
+            Bind::Key(v) => self.key.just_pressed(v),
+            Bind::Button(v) => self.button.just_pressed(v),
+            Bind::Pad(v) => self
+                .gamepads
+                .iter()
+                .any(|gamepad| self.pad_button.just_pressed(GamepadButton::new(gamepad, v))),
+        }
+    }
+
+    pub fn any_pressed(&self) -> bool {
+        self.key.get_pressed().next().is_some()
+            || self.button.get_pressed().next().is_some()
+            || self.pad_button.get_pressed().next().is_some()
+    }
+
+    pub fn any_just_pressed(&self) -> Option<Bind> {
+        let key = self.key.get_just_pressed().next().map(|v| Bind::Key(*v));
+        let button = || {
+            self.button
+                .get_just_pressed()
+                .next()
+                .map(|v| Bind::Button(*v))
+        };
+        let pad = || {
+            let pad_button = self.pad_button.get_just_pressed().next();
+            pad_button.map(|v| Bind::Pad(v.button_type))
+        };
+        key.or_else(button).or_else(pad)
+    }
+
+    fn left_stick(&self, dead_zone: f32) -> Vec2 {
+        let axis = |gamepad, axis_type| {
+            let axis = GamepadAxis::new(gamepad, axis_type);
+            self.pad_axis.get(axis).unwrap_or(0.0)
+        };
+        let mut sum = Vec2::ZERO;
+        for gamepad in self.gamepads.iter() {
+            let x = axis(gamepad, GamepadAxisType::LeftStickX);
+            let y = axis(gamepad, GamepadAxisType::LeftStickY);
+            sum += apply_dead_zone(Vec2::new(x, y), dead_zone);
+        }
+        sum.clamp_length_max(1.0)
     }
 }
 
-fn input(
-    key: Res<Input<KeyCode>>,
-    button: Res<Input<MouseButton>>,
+fn apply_dead_zone(stick: Vec2, dead_zone: f32) -> Vec2 {
+    let length = stick.length();
+    if length <= dead_zone {
+        return Vec2::ZERO;
+    }
+    let scaled = ((length - dead_zone) / (1.0 - dead_zone)).min(1.0);
+    stick / length * scaled
+}
+
+/// Part of the screen width on each side where the cursor thrusts.
+const MOUSE_EDGE: f32 = 0.15;
+
+pub fn input(
+    devices: Devices,
     bindings: Res<Bindings>,
-    mut commands: Commands,
+    settings: Res<Settings>,
+    mut current: ResMut<Controls>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
 ) {
-    let get = |x| match x {
-        Bind::Key(v) => key.pressed(v),
-        Bind::_Button(v) => button.pressed(v),
+    // Mouse buttons only count in mouse control.
+    let get = |binds: &Vec<Bind>| {
+        binds
+            .iter()
+            .filter(|bind| settings.mouse_control || !bind.is_mouse())
+            .any(|bind| devices.pressed(bind))
+    };
+    let mut controls = Controls {
+        move_up: get(&bindings.move_up),
+        move_down: get(&bindings.move_down),
+        move_left: get(&bindings.move_left),
+        move_right: get(&bindings.move_right),
+        shoot: get(&bindings.shoot),
+        rescue: get(&bindings.rescue),
+        analog: devices.left_stick(bindings.dead_zone),
+        target_y: None,
     };
-    commands.insert_resource(Controls {
-        move_up: get(bindings.move_up),
-        move_down: get(bindings.move_down),
-        move_left: get(bindings.move_left),
-        move_right: get(bindings.move_right),
-        shoot: get(bindings.shoot),
-        rescue: get(bindings.rescue),
-    });
+    if settings.mouse_control {
+        let cursor = window_query
+            .get_single()
+            .ok()
+            .and_then(|window| Some((window.cursor_position()?, window.width())));
+        if let (Some((cursor, width)), Ok((camera, camera_transform))) =
+            (cursor, camera_query.get_single())
+        {
+            let x = cursor.x / width;
+            let edge = if x < MOUSE_EDGE {
+                -(MOUSE_EDGE - x) / MOUSE_EDGE
+            } else if x > 1.0 - MOUSE_EDGE {
+                (x - (1.0 - MOUSE_EDGE)) / MOUSE_EDGE
+            } else {
+                0.0
+            };
+            controls.analog.x = (controls.analog.x + edge).clamp(-1.0, 1.0);
+            controls.target_y = camera
+                .viewport_to_world_2d(camera_transform, cursor)
+                .map(|world| world.y);
+        }
+    }
+    *current = controls;
+}
+
+fn try_pausing(
+    devices: Devices,
+    bindings: Res<Bindings>,
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+) {
+    if bindings.pause.iter().any(|bind| devices.just_pressed(bind)) {
+        let next_state = match state.get() {
+            GameState::Playing => GameState::Paused,
+            GameState::Paused => GameState::Playing,
+            _ => return,
+        };
+        commands.insert_resource(NextState(Some(next_state)));
+    }
+}
+
+fn gamepad_connections(
+    mut events: EventReader<GamepadConnectionEvent>,
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+) {
+    for event in events.read() {
+        match &event.connection {
+            GamepadConnection::Connected(info) => {
+                info!("gamepad {} connected: {}", event.gamepad.id, info.name);
+            }
+            GamepadConnection::Disconnected => {
+                info!("gamepad {} disconnected", event.gamepad.id);
+                if *state.get() == GameState::Playing {
+                    commands.insert_resource(NextState(Some(GameState::Paused)));
+                }
+            }
+        }
+    }
 }
@@ -1,45 +1,61 @@
 use bevy::prelude::*;
 
 use crate::{
-    assets::{audio, GameAssets},
-    camera, explosion, game_over, map, minimap, projectile, style, utils,
+    assets::GameAssets,
+    audio::{self, Sound},
+    camera, explosion, game_over, map, minimap, projectile, score,
+    settings::Settings,
+    style, utils,
 };
 use game_over::GameOver;
-use utils::bevy::{hit::*, state::Simulation};
+use utils::bevy::{
+    fixed::{self, Interpolated, Step},
+    game_time::GameTime,
+    hit::*,
+    state::simulating,
+    window,
+};
 use utils::{range::Range, Side};
 
 pub mod input;
+pub mod physics;
+pub mod power_up;
+pub mod rebind;
 mod thrust;
+pub mod weapon;
 
 #[derive(Component)]
 pub struct Player {
     pub facing: Side,
     pub horizontal_speed: f32,
-    next_shot_time: f32,
+    /// Change of `horizontal_speed` over the last tick.
+    pub acceleration: f32,
+    pub vertical_speed: f32,
+    pub next_shot_time: f32,
 }
 
 pub struct Plug;
 
 impl Plugin for Plug {
     fn build(&self, app: &mut App) {
-        app.add_plugins((input::Plug, thrust::Plug))
+        app.add_plugins((input::Plug, power_up::Plug, rebind::Plug, thrust::Plug))
             .add_systems(
-                Update,
+                FixedUpdate,
                 (
-                    (
-                        laser_hit,
-                        detect_hits::<Player>,
-                        movement,
-                        try_shooting,
-                        camera::follow_player,
-                    )
-                        .chain()
-                        .run_if(in_state(Simulation::Running)),
-                    minimap::redraw,
+                    laser_hit,
+                    detect_hits::<Player>,
+                    movement,
+                    try_shooting,
+                    camera::follow_player,
                 )
-                    .chain(),
-            )
-            .add_systems(PostUpdate, try_drawing_on_minimap);
+                    .chain()
+                    .run_if(simulating)
+                    .in_set(Step::Main),
+            );
+        if !utils::bevy::headless(app) {
+            app.add_systems(Update, minimap::redraw.after(fixed::interpolate))
+                .add_systems(PostUpdate, try_drawing_on_minimap);
+        }
     }
 }
 
@@ -57,109 +73,192 @@ fn try_drawing_on_minimap(
     }
 }
 
-pub fn spawn(
-    commands: &mut Commands,
-    assets: &Res<GameAssets>,
-    camera_query: &Query<&Transform, With<Camera>>,
-) {
-    let camera = camera_query.single();
-    commands.spawn((
-        SpriteBundle {
-            transform: Transform {
-                translation: camera.translation,
-                rotation: utils::bevy::angle(-0.25),
+pub fn spawn(commands: &mut Commands, assets: &GameAssets, translation: Vec3) -> Entity {
+    commands
+        .spawn((
+            SpriteBundle {
+                transform: Transform {
+                    translation,
+                    rotation: utils::bevy::angle(-0.25),
+                    ..default()
+                },
+                texture: assets.player_texture.clone(),
                 ..default()
             },
-            texture: assets.player_texture.clone(),
-            ..default()
-        },
-        Player {
-            facing: Side::Right,
-            horizontal_speed: 0.0,
-            next_shot_time: 0.0,
-        },
-        thrust::ThrustBundle::new(assets),
-        map::Confine,
-        Hittable::<projectile::orb::Orb>::new(style::PLAYER_BOUND),
-    ));
+            Player {
+                facing: Side::Right,
+                horizontal_speed: 0.0,
+                acceleration: 0.0,
+                vertical_speed: 0.0,
+                next_shot_time: 0.0,
+            },
+            map::Confine,
+            Interpolated::default(),
+            Hittable::<projectile::orb::Orb>::new(style::PLAYER_BOUND),
+            Hittable::<power_up::Pickup>::new(style::PLAYER_BOUND),
+            power_up::Active::default(),
+        ))
+        .id()
 }
 
 pub const HORIZONTAL_SPEED: f32 = 600.0;
 const VERTICAL_SPEED: f32 = 400.0;
+/// How fast the ship closes the gap to the mouse target, per second.
+const FOLLOW_RATE: f32 = 8.0;
 
-const ACCELERATION: f32 = 1600.0;
+pub const ACCELERATION: f32 = 1600.0;
 const DECELERATION: f32 = 100.0;
+/// Turning around faster than this brakes, slower boosts off.
+const BRAKE_SPEED: f32 = HORIZONTAL_SPEED * 0.25;
 
 fn movement(
     mut player_query: Query<(&mut Transform, &mut Player)>,
     time: Res<Time>,
     controls: Res<input::Controls>,
+    mut sounds: EventWriter<audio::Play>,
+    settings: Option<Res<Settings>>,
+    window_size: Res<window::Size>,
 ) {
+    let arcade = settings
+        .filter(|settings| settings.arcade)
+        .map(|settings| settings.physics);
+    let dt = time.delta_seconds();
     if let Ok((mut transform, mut player)) = player_query.get_single_mut() {
         if let Some(side) = controls.facing() {
             if player.facing != side {
                 player.facing = side;
-                transform.rotate(utils::bevy::angle(0.5));
+                let sound = match player.horizontal_speed.abs() > BRAKE_SPEED {
+                    true => Sound::Brake,
+                    false => Sound::Boost,
+                };
+                sounds.send(audio::Play::at(sound, transform.translation));
             }
         }
         let start = player.horizontal_speed;
-        player.horizontal_speed = if let Some(side) = controls.facing() {
-            let end = side.sign() * HORIZONTAL_SPEED;
-            Range { start, end }.step(ACCELERATION * time.delta_seconds())
-        } else {
-            let end = 0.0;
-            let speed_ratio = player.horizontal_speed.abs() / HORIZONTAL_SPEED;
-            let t = speed_ratio.powf(1.0);
-            Range { start, end }.step(DECELERATION * t * time.delta_seconds())
+        let target = controls
+            .facing()
+            .map(|side| side.sign() * HORIZONTAL_SPEED * controls.horizontal());
+        player.horizontal_speed = match (arcade, target) {
+            (Some(arcade), _) => arcade.horizontal(start, target, dt),
+            (None, Some(end)) => Range { start, end }.step(ACCELERATION * dt),
+            (None, None) => {
+                let end = 0.0;
+                let speed_ratio = player.horizontal_speed.abs() / HORIZONTAL_SPEED;
+                let t = speed_ratio.powf(1.0);
+                Range { start, end }.step(DECELERATION * t * dt)
+            }
+        };
+        let dy = match controls.target_y {
+            Some(target) if controls.vertical() == 0.0 => {
+                let d = target - transform.translation.y;
+                (d * FOLLOW_RATE).clamp(-VERTICAL_SPEED, VERTICAL_SPEED)
+            }
+            _ => controls.vertical() * VERTICAL_SPEED,
+        };
+        player.vertical_speed = match arcade {
+            Some(arcade) => arcade.vertical(player.vertical_speed, dy, dt),
+            None => dy,
         };
-        let dy = controls.vertical() * VERTICAL_SPEED;
-        transform.translation += time.delta_seconds() * Vec3::new(player.horizontal_speed, dy, 0.0);
+        player.acceleration = (player.horizontal_speed - start) / dt;
+        let velocity = Vec3::new(player.horizontal_speed, player.vertical_speed, 0.0);
+        transform.translation += dt * velocity;
+        // Held against the edge by `map::Confine`, so no speed builds up
+        // into it.
+        let (bottom, top) = map::band(window_size.0);
+        let y = transform.translation.y;
+        if (y <= bottom && player.vertical_speed < 0.0) || (y >= top && player.vertical_speed > 0.0)
+        {
+            player.vertical_speed = 0.0;
+        }
+        let facing = match player.facing {
+            Side::Left => 0.25,
+            Side::Right => -0.25,
+        };
+        let tilt = arcade.map_or(0.0, |arcade| arcade.tilt(player.vertical_speed));
+        transform.rotation = utils::bevy::angle(facing + tilt * player.facing.sign());
     }
 }
 
-const SHOOT_DELAY: f32 = 0.3;
-
+#[allow(clippy::too_many_arguments)]
 fn try_shooting(
-    mut player_query: Query<(&Transform, &mut Player)>,
+    mut player_query: Query<(&Transform, &mut Player, &power_up::Active)>,
     mut commands: Commands,
     assets: Res<GameAssets>,
-    time: Res<Time>,
+    time: Res<GameTime>,
     controls: Res<input::Controls>,
+    mut stats: ResMut<score::WaveStats>,
+    mut sounds: EventWriter<audio::Play>,
+    settings: Option<Res<Settings>>,
 ) {
     let elapsed = time.elapsed_seconds();
-    if let Ok((transform, mut player)) = player_query.get_single_mut() {
+    let beam = settings.is_some_and(|settings| settings.beam);
+    if let Ok((transform, mut player, active)) = player_query.get_single_mut() {
         if controls.shoot && player.next_shot_time <= elapsed {
-            commands.spawn(audio(assets.laser_audio.clone(), style::VOLUME));
+            let weapon = weapon::Weapon::armed(active, elapsed);
+            stats.shots += 1;
+            sounds.send(audio::Play::at(Sound::Laser, transform.translation));
             let angle = match player.facing {
                 Side::Left => 0.5,
                 Side::Right => 0.0,
             };
             let direction = Vec3::X * player.facing.sign();
+            if beam {
+                let front = transform.translation + style::PLAYER_FRONT_OFFSET * direction;
+                for offset in weapon.offsets() {
+                    commands.spawn(projectile::beam::bundle(
+                        front + Vec3::Y * offset,
+                        player.facing.sign(),
+                        weapon.piercing,
+                        elapsed,
+                    ));
+                }
+                player.next_shot_time = elapsed + weapon.delay;
+                return;
+            }
             let off = style::PLAYER_FRONT_OFFSET + style::LASER_BOUND.x * 0.5;
             let position = transform.translation + off * direction;
             let speed = projectile::laser::SPEED;
             let color = utils::bevy::bloom_hue((elapsed * 120.0) % 360.0);
-            commands.spawn(projectile::Bundle::new(
-                &assets,
-                position,
-                angle,
-                speed,
-                color,
-                projectile::laser::Laser,
-            ));
-            player.next_shot_time = elapsed + SHOOT_DELAY;
+            for offset in weapon.offsets() {
+                let mut laser = commands.spawn(projectile::Bundle::new(
+                    &assets,
+                    position + Vec3::Y * offset,
+                    angle,
+                    speed,
+                    color,
+                    projectile::laser::Laser,
+                ));
+                if weapon.piercing {
+                    laser.insert(projectile::laser::Piercing);
+                }
+            }
+            player.next_shot_time = elapsed + weapon.delay;
         }
     }
 }
 
 fn laser_hit(
-    query: Query<(Entity, &Transform, &Hittable<projectile::orb::Orb>), With<Player>>,
+    query: Query<
+        (
+            Entity,
+            &Transform,
+            &Hittable<projectile::orb::Orb>,
+            &power_up::Active,
+        ),
+        With<Player>,
+    >,
     mut commands: Commands,
     mut explosion_event: EventWriter<explosion::At>,
     mut game_over_event: EventWriter<GameOver>,
+    time: Res<GameTime>,
 ) {
-    for (player_entity, player, hittable) in query.iter() {
-        if let Some(_) = hittable.hit_entity {
+    for (player_entity, player, hittable, active) in query.iter() {
+        let Some(orb) = hittable.hit_entity else {
+            continue;
+        };
+        if active.on(power_up::Kind::Shield, time.elapsed_seconds()) {
+            commands.entity(orb).despawn();
+        } else {
             commands.entity(player_entity).despawn();
             explosion_event.send(explosion::At {
                 position: player.translation,
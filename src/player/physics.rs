@@ -0,0 +1,157 @@
+use super::VERTICAL_SPEED;
+use crate::utils::range::Range;
+use serde::{Deserialize, Serialize};
+
+/// The optional arcade flight model, with momentum on both axes. Speeds
+/// are in units a second, rates in units a second squared.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Arcade {
+    /// Horizontal speed gained while thrusting.
+    pub thrust: f32,
+    /// Horizontal speed lost while thrusting against the motion.
+    pub brake: f32,
+    /// Horizontal speed lost while coasting.
+    pub drag: f32,
+    /// Vertical speed gained or lost towards the wanted one.
+    pub climb: f32,
+    /// Tilt at full vertical speed, in turns.
+    pub bank: f32,
+}
+
+impl Default for Arcade {
+    fn default() -> Self {
+        Self {
+            thrust: 1200.0,
+            brake: 2400.0,
+            drag: 300.0,
+            climb: 1600.0,
+            bank: 0.05,
+        }
+    }
+}
+
+impl Arcade {
+    /// Horizontal speed `dt` after `speed`, thrusting towards `target` or
+    /// coasting without one. Thrusting the other way brakes to a stop first.
+    pub fn horizontal(&self, speed: f32, target: Option<f32>, dt: f32) -> f32 {
+        let (end, rate) = match target {
+            Some(target) if speed * target < 0.0 => (0.0, self.brake),
+            Some(target) => (target, self.thrust),
+            None => (0.0, self.drag),
+        };
+        Range { start: speed, end }.step(rate * dt)
+    }
+
+    /// Vertical speed `dt` after `speed`, going for `target`.
+    pub fn vertical(&self, speed: f32, target: f32, dt: f32) -> f32 {
+        Range {
+            start: speed,
+            end: target,
+        }
+        .step(self.climb * dt)
+    }
+
+    /// Tilt of the ship in turns at `vertical_speed`, nose up while climbing
+    /// when facing right.
+    pub fn tilt(&self, vertical_speed: f32) -> f32 {
+        (vertical_speed / VERTICAL_SPEED).clamp(-1.0, 1.0) * self.bank
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        enemy::lander::Lander,
+        harness::{Harness, WINDOW_SIZE},
+        map,
+        player::{input::Controls, Player},
+        settings::Settings,
+    };
+    use bevy::prelude::*;
+
+    const DT: f32 = 1.0 / 64.0;
+
+    /// Ticks `horizontal` takes from `speed` until `done`.
+    fn ticks(arcade: &Arcade, mut speed: f32, target: f32, done: impl Fn(f32) -> bool) -> u32 {
+        let mut ticks = 0;
+        while !done(speed) {
+            speed = arcade.horizontal(speed, Some(target), DT);
+            ticks += 1;
+            assert!(ticks < 10_000, "never got there");
+        }
+        ticks
+    }
+
+    #[test]
+    fn reversing_brakes_before_thrusting_back() {
+        let arcade = Arcade::default();
+        let speed = 600.0;
+        let stop = ticks(&arcade, speed, -600.0, |speed| speed <= 0.0);
+        assert_eq!(stop, (speed / (arcade.brake * DT)).ceil() as u32);
+        // Not past a stop in the same tick.
+        assert_eq!(arcade.horizontal(10.0, Some(-600.0), DT), 0.0);
+        let back = ticks(&arcade, 0.0, -600.0, |speed| speed <= -600.0);
+        assert_eq!(back, (600.0 / (arcade.thrust * DT)).ceil() as u32);
+        assert!(back > stop);
+    }
+
+    #[test]
+    fn coasting_drags_to_a_stop() {
+        let arcade = Arcade::default();
+        let speed = arcade.horizontal(-300.0, None, DT);
+        assert_eq!(speed, -300.0 + arcade.drag * DT);
+        assert_eq!(arcade.horizontal(1.0, None, DT), 0.0);
+    }
+
+    #[test]
+    fn climbing_takes_time_and_banks() {
+        let arcade = Arcade::default();
+        let speed = arcade.vertical(0.0, VERTICAL_SPEED, DT);
+        assert_eq!(speed, arcade.climb * DT);
+        let mut speed = speed;
+        for _ in 0..64 {
+            speed = arcade.vertical(speed, VERTICAL_SPEED, DT);
+        }
+        assert_eq!(speed, VERTICAL_SPEED);
+        assert_eq!(arcade.tilt(speed), arcade.bank);
+        assert_eq!(arcade.tilt(-2.0 * VERTICAL_SPEED), -arcade.bank);
+        assert_eq!(arcade.tilt(0.0), 0.0);
+    }
+
+    #[test]
+    fn ceiling_stops_the_climb() {
+        let mut harness = Harness::new(WINDOW_SIZE);
+        harness.app.insert_resource(Settings {
+            arcade: true,
+            ..default()
+        });
+        harness.start(4);
+        harness.clear();
+        harness.spawn_enemy(Vec3::new(map::SIZE * 0.5, 300.0, 0.0), Lander, None);
+        let vertical_speed = |harness: &mut Harness| {
+            let (player, _) = harness.player().unwrap();
+            harness
+                .app
+                .world
+                .get::<Player>(player)
+                .unwrap()
+                .vertical_speed
+        };
+        let up = Controls {
+            move_up: true,
+            ..default()
+        };
+        harness.step(3 * 64, up);
+        let (_, position) = harness.player().unwrap();
+        assert_eq!(position.y, map::band(WINDOW_SIZE).1);
+        assert_eq!(vertical_speed(&mut harness), 0.0);
+        let down = Controls {
+            move_down: true,
+            ..default()
+        };
+        harness.step(1, down);
+        assert!(vertical_speed(&mut harness) < 0.0);
+    }
+}
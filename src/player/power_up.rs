@@ -0,0 +1,308 @@
+use super::{weapon::Weapon, Player};
+use crate::{map, rng::GameRng, style, utils};
+use bevy::{prelude::*, window::PrimaryWindow};
+use serde::{Deserialize, Serialize};
+use utils::bevy::{
+    fixed::{Interpolated, Step},
+    game_time::GameTime,
+    hit::*,
+    state::simulating,
+    DespawnTime,
+};
+
+/// Chance of a shot down enemy leaving a pickup behind.
+pub const DROP_CHANCE: f32 = 0.15;
+/// Seconds a pickup waits to be collected.
+const PICKUP_LIFETIME: f32 = 10.0;
+const PICKUP_SIZE: Vec2 = Vec2::new(20.0, 20.0);
+/// Shots come this much sooner with rapid fire.
+const RAPID_FIRE: f32 = 0.5;
+const SHIELD_RADIUS: f32 = 36.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Kind {
+    RapidFire,
+    TwinBeam,
+    Piercing,
+    Shield,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 4] = [
+        Kind::RapidFire,
+        Kind::TwinBeam,
+        Kind::Piercing,
+        Kind::Shield,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Kind::RapidFire => "rapid",
+            Kind::TwinBeam => "twin",
+            Kind::Piercing => "pierce",
+            Kind::Shield => "shield",
+        }
+    }
+
+    /// Seconds it lasts once collected.
+    pub fn duration(&self) -> f32 {
+        match self {
+            Kind::RapidFire => 10.0,
+            Kind::TwinBeam => 12.0,
+            Kind::Piercing => 8.0,
+            Kind::Shield => 6.0,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Kind::RapidFire => Color::YELLOW,
+            Kind::TwinBeam => Color::CYAN,
+            Kind::Piercing => Color::FUCHSIA,
+            Kind::Shield => Color::WHITE,
+        }
+    }
+
+    pub fn upgrade(&self, weapon: Weapon) -> Weapon {
+        match self {
+            Kind::RapidFire => Weapon {
+                delay: weapon.delay * RAPID_FIRE,
+                ..weapon
+            },
+            Kind::TwinBeam => Weapon { beams: 2, ..weapon },
+            Kind::Piercing => Weapon {
+                piercing: true,
+                ..weapon
+            },
+            Kind::Shield => weapon,
+        }
+    }
+}
+
+/// Power-up waiting to be flown into.
+#[derive(Component)]
+pub struct Pickup {
+    pub kind: Kind,
+}
+
+impl Bound for Pickup {
+    fn bound() -> Vec2 {
+        PICKUP_SIZE
+    }
+}
+
+/// Power-ups of the ship, by the game time they run out at.
+#[derive(Component, Default)]
+pub struct Active {
+    until: [f32; Kind::ALL.len()],
+}
+
+impl Active {
+    pub fn on(&self, kind: Kind, now: f32) -> bool {
+        self.until[kind as usize] > now
+    }
+
+    /// Seconds `kind` has left at `now`.
+    pub fn left(&self, kind: Kind, now: f32) -> f32 {
+        (self.until[kind as usize] - now).max(0.0)
+    }
+
+    /// Starts `kind` over for its full duration.
+    pub fn grant(&mut self, kind: Kind, now: f32) {
+        self.until[kind as usize] = now + kind.duration();
+    }
+
+    pub fn set_left(&mut self, kind: Kind, now: f32, left: f32) {
+        self.until[kind as usize] = now + left;
+    }
+}
+
+pub struct Plug;
+
+impl Plugin for Plug {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (detect_hits::<Pickup>, collect)
+                .chain()
+                .run_if(simulating)
+                .in_set(Step::Main),
+        );
+        if !utils::bevy::headless(app) {
+            app.add_systems(Startup, spawn_indicator)
+                .add_systems(Update, (update_indicator, draw_shield));
+        }
+    }
+}
+
+/// Leaves a random power-up at `translation`, now and then.
+pub fn try_dropping(commands: &mut Commands, rng: &mut GameRng, translation: Vec3, now: f32) {
+    if rng.f32() >= DROP_CHANCE {
+        return;
+    }
+    let i = (rng.f32() * Kind::ALL.len() as f32) as usize;
+    let kind = Kind::ALL[i.min(Kind::ALL.len() - 1)];
+    commands.spawn((
+        SpriteBundle {
+            transform: Transform::from_translation(translation)
+                .with_rotation(utils::bevy::angle(0.125)),
+            sprite: Sprite {
+                color: kind.color(),
+                custom_size: Some(PICKUP_SIZE),
+                ..default()
+            },
+            ..default()
+        },
+        Pickup { kind },
+        map::Scroll,
+        Interpolated::default(),
+        DespawnTime {
+            elapsed_seconds: now + PICKUP_LIFETIME,
+        },
+    ));
+}
+
+fn collect(
+    mut commands: Commands,
+    mut player_query: Query<(&Hittable<Pickup>, &mut Active), With<Player>>,
+    pickup_query: Query<&Pickup>,
+    game_time: Res<GameTime>,
+) {
+    for (hittable, mut active) in player_query.iter_mut() {
+        let Some(entity) = hittable.hit_entity else {
+            continue;
+        };
+        if let Ok(pickup) = pickup_query.get(entity) {
+            active.grant(pickup.kind, game_time.elapsed_seconds());
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[derive(Component)]
+struct Indicator;
+
+const INDICATOR_FONT_SIZE: f32 = 24.0;
+
+fn spawn_indicator(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        TextBundle::from_sections(Kind::ALL.map(|kind| {
+            TextSection::from_style(TextStyle {
+                font: asset_server.load(style::FONT),
+                font_size: INDICATOR_FONT_SIZE,
+                color: kind.color(),
+            })
+        }))
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            ..default()
+        }),
+        Indicator,
+    ));
+}
+
+/// Lists the power-ups of the ship with the seconds they have left, right of
+/// the minimap.
+fn update_indicator(
+    mut query: Query<(&mut Text, &mut Style), With<Indicator>>,
+    player_query: Query<&Active, With<Player>>,
+    game_time: Res<GameTime>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    let (mut text, mut style) = query.single_mut();
+    let window = window_query.single();
+    style.top = Val::Px((window.height() * style::MINIMAP_SIZE.y - INDICATOR_FONT_SIZE) * 0.5);
+    style.left = Val::Px(window.width() * (1.0 - style::TEXT_SPACE) + 15.0);
+    let now = game_time.elapsed_seconds();
+    let active = player_query.get_single().ok();
+    for (section, kind) in text.sections.iter_mut().zip(Kind::ALL) {
+        section.value = match active.filter(|active| active.on(kind, now)) {
+            Some(active) => format!("{} {:.0} ", kind.name(), active.left(kind, now).ceil()),
+            None => String::new(),
+        };
+    }
+}
+
+fn draw_shield(
+    mut gizmos: Gizmos,
+    player_query: Query<(&Transform, &Active), With<Player>>,
+    game_time: Res<GameTime>,
+) {
+    if let Ok((transform, active)) = player_query.get_single() {
+        if active.on(Kind::Shield, game_time.elapsed_seconds()) {
+            gizmos.circle_2d(
+                transform.translation.truncate(),
+                SHIELD_RADIUS,
+                Kind::Shield.color(),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        enemy::lander::Lander,
+        harness::{Harness, WINDOW_SIZE},
+        player::input::Controls,
+        projectile::{self, orb::Orb},
+        utils::bevy::state::GameState,
+    };
+
+    /// A run with a single enemy far away, so the wave goes on.
+    fn quiet_run() -> Harness {
+        let mut harness = Harness::new(WINDOW_SIZE);
+        harness.start(5);
+        harness.clear();
+        let far = Vec3::new(crate::map::SIZE * 0.5, 300.0, 0.0);
+        harness.spawn_enemy(far, Lander, None);
+        harness
+    }
+
+    fn active(harness: &mut Harness) -> &Active {
+        let world = &mut harness.app.world;
+        world
+            .query_filtered::<&Active, With<Player>>()
+            .single(world)
+    }
+
+    #[test]
+    fn pickup_grants_its_power_up() {
+        let mut harness = quiet_run();
+        let (_, position) = harness.player().unwrap();
+        harness.app.world.spawn((
+            SpatialBundle::from_transform(Transform::from_translation(position)),
+            Pickup { kind: Kind::Shield },
+        ));
+        harness.step(2, Controls::default());
+        assert_eq!(harness.count::<Pickup>(), 0);
+        let now = harness.app.world.resource::<GameTime>().elapsed_seconds();
+        assert!(active(&mut harness).on(Kind::Shield, now));
+        assert!(!active(&mut harness).on(Kind::Piercing, now));
+    }
+
+    #[test]
+    fn shield_stops_orbs() {
+        let mut harness = quiet_run();
+        let (player, position) = harness.player().unwrap();
+        let now = harness.app.world.resource::<GameTime>().elapsed_seconds();
+        let world = &mut harness.app.world;
+        world
+            .get_mut::<Active>(player)
+            .unwrap()
+            .grant(Kind::Shield, now);
+        world.spawn(projectile::Bundle::new(
+            &Default::default(),
+            position,
+            0.0,
+            0.0,
+            Color::RED,
+            Orb,
+        ));
+        harness.step(2, Controls::default());
+        assert_eq!(harness.count::<Orb>(), 0);
+        assert_eq!(harness.state(), GameState::Playing);
+        assert!(harness.player().is_some());
+    }
+}
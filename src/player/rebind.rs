@@ -0,0 +1,86 @@
+use super::input::{Action, Bind, Bindings, Devices};
+use bevy::prelude::*;
+
+/// Gives up on a capture instead of being bound.
+const CANCEL: Bind = Bind::Key(KeyCode::Escape);
+
+#[derive(Resource, Default)]
+pub struct Rebinding {
+    pub action: Option<Action>,
+    /// Set once every input is released, so the click that started the
+    /// capture is not taken as the new bind.
+    armed: bool,
+    pub message: String,
+}
+
+impl Rebinding {
+    pub fn start(&mut self, action: Action) {
+        self.action = Some(action);
+        self.armed = false;
+        self.message = format!(
+            "press new {} input, {} to cancel",
+            action.label(),
+            CANCEL.name()
+        );
+    }
+
+    fn stop(&mut self) {
+        self.action = None;
+        self.message.clear();
+    }
+}
+
+pub struct Plug;
+
+impl Plugin for Plug {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rebinding>();
+        if !crate::utils::bevy::headless(app) {
+            app.add_systems(Update, capture);
+        }
+    }
+}
+
+pub fn idle(rebinding: Res<Rebinding>) -> bool {
+    rebinding.action.is_none()
+}
+
+pub fn capture(
+    devices: Devices,
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<Bindings>,
+    interaction_query: Query<&Interaction>,
+) {
+    let Some(action) = rebinding.action else {
+        return;
+    };
+    if !rebinding.armed {
+        rebinding.armed = !devices.any_pressed();
+        return;
+    }
+    if devices.just_pressed(&CANCEL) {
+        rebinding.stop();
+        return;
+    }
+    // Clicks on the menu are for the menu.
+    let on_menu = || {
+        interaction_query
+            .iter()
+            .any(|interaction| *interaction != Interaction::None)
+    };
+    if let Some(bind) = devices.any_just_pressed() {
+        if matches!(bind, Bind::Button(_)) && on_menu() {
+            return;
+        }
+        match bindings.conflict(action, bind) {
+            Some(other) => {
+                rebinding.message = format!("{} is used by {}", bind.name(), other.label());
+            }
+            None => {
+                bindings.rebind(action, bind);
+                bindings.save();
+                rebinding.stop();
+            }
+        }
+    }
+}
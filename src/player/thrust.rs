@@ -1,52 +1,59 @@
 use bevy::prelude::*;
 
-use super::{input, Player};
-use crate::{assets::GameAssets, style, utils};
+use super::{input, Player, ACCELERATION, HORIZONTAL_SPEED};
+use crate::{audio::Bus, rng, settings::Settings, style, utils};
+use utils::bevy::state::GameState;
 
+/// Engine level per second while the engine has something to follow.
+const ADJUST_SPEED: f32 = 4.0;
+/// Engine level per second when the player dies or the game pauses.
+const FADE_OUT_SPEED: f32 = 8.0;
+
+/// The engine loop. It lives apart from the player so it can fade out
+/// after the player is gone and starts from silence every run.
 #[derive(Component)]
-pub struct Thrust {
+pub struct Engine {
     volume: f32,
 }
 
-#[derive(Bundle)]
-pub struct ThrustBundle {
-    audio: AudioBundle,
-    thrust: Thrust,
-}
-
-impl ThrustBundle {
-    pub fn new(assets: &GameAssets) -> ThrustBundle {
-        ThrustBundle {
-            audio: AudioBundle {
-                source: assets.thrust_audio.clone(),
-                settings: PlaybackSettings::LOOP.with_volume(utils::bevy::volume(0.0)),
-            },
-            thrust: Thrust { volume: 0.0 },
-        }
-    }
-}
-
 pub struct Plug;
 
 impl Plugin for Plug {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (spawn_particle, set_volume));
+        if utils::bevy::headless(app) {
+            return;
+        }
+        app.add_systems(Startup, spawn_engine)
+            .add_systems(Update, (spawn_particle, set_volume));
     }
 }
 
+fn spawn_engine(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        AudioBundle {
+            source: asset_server.load(style::THRUST_SOUND),
+            settings: PlaybackSettings::LOOP
+                .with_volume(utils::bevy::volume(0.0))
+                .paused(),
+        },
+        Engine { volume: 0.0 },
+    ));
+}
+
 fn spawn_particle(
     player_query: Query<(Entity, &Transform, &Player)>,
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    time: Res<Time>,
+    time: Res<utils::bevy::game_time::GameTime>,
     controls: Res<input::Controls>,
+    mut rng: ResMut<rng::CosmeticRng>,
 ) {
     if let Ok((player_entity, transform, player)) = player_query.get_single() {
         if controls.thrust() {
             let offset = {
                 const SPREAD: f32 = 5.0;
                 let x = player.facing.sign() * style::THRUST_OFFSET;
-                let y = (rand::random::<f32>() * 2.0 - 1.0) * SPREAD;
+                let y = (rng.f32() * 2.0 - 1.0) * SPREAD;
                 Vec3::new(x, y, 0.0)
             };
             let mut translation = transform.translation + offset;
@@ -64,7 +71,7 @@ fn spawn_particle(
                     },
                     texture: asset_server.load(utils::variant(
                         style::SMOKE_TEXTURE,
-                        format!("{:02}", rand::random::<u32>() % 25),
+                        format!("{:02}", rng.u32() % 25),
                     )),
                     sprite: Sprite {
                         color: utils::bevy::bloom_hue(30.0),
@@ -84,18 +91,43 @@ fn spawn_particle(
     }
 }
 
+/// Louder and higher the faster the player goes and the harder it speeds up
+/// or slows down, silent without a player or while the game stands still.
 fn set_volume(
-    mut thrust_query: Query<(&AudioSink, &mut Thrust)>,
-    time: Res<Time>,
-    controls: Res<input::Controls>,
+    mut engine_query: Query<(&AudioSink, &mut Engine)>,
+    player_query: Query<&Player>,
+    state: Res<State<GameState>>,
+    time: Res<Time<Real>>,
+    settings: Res<Settings>,
 ) {
-    if let Ok((audio, mut thrust)) = thrust_query.get_single_mut() {
-        const ADJUST_SPEED: f32 = 4.0;
-        thrust.volume = utils::range::Range {
-            start: thrust.volume,
-            end: if controls.thrust() { 1.0 } else { 0.0 },
+    let Ok((sink, mut engine)) = engine_query.get_single_mut() else {
+        return;
+    };
+    let player = player_query.get_single().ok();
+    let (target, pitch, speed) = match player {
+        Some(player) if state.get().simulating() => {
+            let speed = player.horizontal_speed.abs() / HORIZONTAL_SPEED;
+            let push = (player.acceleration.abs() / ACCELERATION).min(1.0);
+            let target = (0.5 * speed + 0.5 * push).min(1.0);
+            (target, 0.7 + 0.5 * speed + 0.2 * push, ADJUST_SPEED)
         }
-        .mix(ADJUST_SPEED * time.delta_seconds());
-        audio.set_volume(thrust.volume * style::VOLUME);
+        _ => (0.0, sink.speed(), FADE_OUT_SPEED),
+    };
+    engine.volume = utils::range::Range {
+        start: engine.volume,
+        end: target,
+    }
+    .step(speed * time.delta_seconds());
+    sink.set_volume(engine.volume * settings.bus(Bus::Engine));
+    let pitch = utils::range::Range {
+        start: sink.speed(),
+        end: pitch,
+    }
+    .mix((ADJUST_SPEED * time.delta_seconds()).min(1.0));
+    sink.set_speed(pitch);
+    match engine.volume > 0.0 {
+        true if sink.is_paused() => sink.play(),
+        false if !sink.is_paused() => sink.pause(),
+        _ => {}
     }
 }
@@ -0,0 +1,60 @@
+use super::power_up::{Active, Kind};
+
+/// How the ship's laser fires.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Weapon {
+    /// Seconds between shots.
+    pub delay: f32,
+    /// Lasers side by side per shot.
+    pub beams: u32,
+    /// Whether lasers go on through whatever they hit.
+    pub piercing: bool,
+}
+
+/// The weapon without power-ups.
+pub const LASER: Weapon = Weapon {
+    delay: 0.3,
+    beams: 1,
+    piercing: false,
+};
+
+/// Vertical distance between lasers fired side by side.
+const BEAM_SPACING: f32 = 14.0;
+
+impl Weapon {
+    /// [`LASER`] upgraded by the power-ups `active` at `now`.
+    pub fn armed(active: &Active, now: f32) -> Self {
+        Kind::ALL
+            .into_iter()
+            .filter(|kind| active.on(*kind, now))
+            .fold(LASER, |weapon, kind| kind.upgrade(weapon))
+    }
+
+    /// Vertical offsets of the lasers of a shot.
+    pub fn offsets(&self) -> impl Iterator<Item = f32> {
+        let middle = (self.beams - 1) as f32 * 0.5;
+        (0..self.beams).map(move |i| (i as f32 - middle) * BEAM_SPACING)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn power_ups_stack_until_they_run_out() {
+        let mut active = Active::default();
+        assert_eq!(Weapon::armed(&active, 0.0), LASER);
+        active.grant(Kind::RapidFire, 0.0);
+        active.grant(Kind::TwinBeam, 5.0);
+        let weapon = Weapon::armed(&active, 6.0);
+        assert!(weapon.delay < LASER.delay);
+        assert_eq!(weapon.offsets().collect::<Vec<_>>(), [-7.0, 7.0]);
+        assert!(!weapon.piercing);
+        let later = 5.0 + Kind::TwinBeam.duration() - 0.5;
+        assert!(later > Kind::RapidFire.duration());
+        assert_eq!(Weapon::armed(&active, later).delay, LASER.delay);
+        assert_eq!(Weapon::armed(&active, later).beams, 2);
+        assert_eq!(Weapon::armed(&active, later + 1.0), LASER);
+    }
+}
@@ -0,0 +1,221 @@
+use bevy::prelude::*;
+
+use crate::{enemy::Enemy, map, utils};
+use utils::bevy::{
+    fixed::{Interpolated, Step},
+    game_time::GameTime,
+    hit::*,
+    state::simulating,
+    window, DespawnTime,
+};
+
+use super::laser::Laser;
+
+/// Seconds the beam takes to reach its full length.
+const GROW_TIME: f32 = 0.08;
+/// Seconds the beam stays on screen.
+const LIFETIME: f32 = 0.2;
+/// Height of the beam for hitting things.
+const WIDTH: f32 = 4.0;
+/// Degrees of hue the beam flickers through a second.
+const HUE_RATE: f32 = 2400.0;
+
+/// The classic streak fired ahead of the ship, hitting at once whatever it
+/// reaches first.
+#[derive(Component)]
+pub struct Beam {
+    /// 1 to the right, -1 to the left.
+    sign: f32,
+    /// Goes on through whatever it hits.
+    piercing: bool,
+    /// From its start to where it ends, known once it struck.
+    length: Option<f32>,
+    fired_at: f32,
+}
+
+pub fn bundle(translation: Vec3, sign: f32, piercing: bool, now: f32) -> impl Bundle {
+    (
+        SpatialBundle::from_transform(Transform::from_translation(translation)),
+        Beam {
+            sign,
+            piercing,
+            length: None,
+            fired_at: now,
+        },
+        map::Scroll,
+        Interpolated::default(),
+        DespawnTime {
+            elapsed_seconds: now + LIFETIME,
+        },
+    )
+}
+
+pub struct Plug;
+
+impl Plugin for Plug {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            strike
+                .after(detect_hits::<Laser>)
+                .run_if(simulating)
+                .in_set(Step::Main),
+        );
+        if !utils::bevy::headless(app) {
+            app.add_systems(Update, draw);
+        }
+    }
+}
+
+/// Targets crossed by a beam from `start` going `sign` way for `range`, by
+/// their `(entity, position, hitbox)`, nearest first along with how far.
+pub fn along(
+    start: Vec2,
+    sign: f32,
+    range: f32,
+    targets: impl IntoIterator<Item = (Entity, Vec2, Vec2)>,
+) -> Vec<(Entity, f32)> {
+    let mut hits: Vec<(Entity, f32)> = targets
+        .into_iter()
+        .filter(|(_, position, hitbox)| (position.y - start.y).abs() <= (hitbox.y + WIDTH) * 0.5)
+        .filter_map(|(entity, position, hitbox)| {
+            let near = (position.x - start.x) * sign - hitbox.x * 0.5;
+            (near + hitbox.x >= 0.0 && near <= range).then_some((entity, near.max(0.0)))
+        })
+        .collect();
+    hits.sort_by(|a, b| a.1.total_cmp(&b.1));
+    hits
+}
+
+/// Marks what new beams reach as hit by a laser, so they go down just the
+/// same.
+pub fn strike(
+    mut beam_query: Query<(Entity, &Transform, &mut Beam)>,
+    mut target_query: Query<(Entity, &Transform, &mut Hittable<Laser>), With<Enemy>>,
+    camera_query: Query<&Transform, With<Camera>>,
+    window_size: Res<window::Size>,
+) {
+    let camera_x = camera_query.single().translation.x;
+    for (beam_entity, transform, mut beam) in beam_query.iter_mut() {
+        if beam.length.is_some() {
+            continue;
+        }
+        let start = transform.translation.truncate();
+        // Up to the edge of the window ahead.
+        let edge = camera_x + beam.sign * window_size.0.x * 0.5;
+        let range = ((edge - start.x) * beam.sign).max(0.0);
+        let targets = target_query.iter().map(|(entity, transform, hittable)| {
+            (entity, transform.translation.truncate(), hittable.hitbox)
+        });
+        let hits = along(start, beam.sign, range, targets);
+        let hits = match beam.piercing {
+            true => &hits[..],
+            false => &hits[..hits.len().min(1)],
+        };
+        beam.length = Some(match (beam.piercing, hits.first()) {
+            (false, Some((_, distance))) => *distance,
+            _ => range,
+        });
+        for (entity, _) in hits {
+            if let Ok((_, _, mut hittable)) = target_query.get_mut(*entity) {
+                hittable.hit_entity = Some(beam_entity);
+            }
+        }
+    }
+}
+
+fn draw(mut gizmos: Gizmos, query: Query<(&Transform, &Beam)>, game_time: Res<GameTime>) {
+    let now = game_time.elapsed_seconds();
+    let color = utils::bevy::bloom_hue((now * HUE_RATE) % 360.0);
+    for (transform, beam) in query.iter() {
+        let Some(length) = beam.length else {
+            continue;
+        };
+        let grown = ((now - beam.fired_at) / GROW_TIME).min(1.0);
+        let start = transform.translation.truncate();
+        let end = start + Vec2::X * beam.sign * length * grown;
+        gizmos.line_2d(start, end, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        enemy::lander::Lander,
+        harness::{Harness, WINDOW_SIZE},
+        player::input::Controls,
+    };
+
+    const HITBOX: Vec2 = Vec2::new(40.0, 40.0);
+
+    fn target(index: u32, x: f32, y: f32) -> (Entity, Vec2, Vec2) {
+        (Entity::from_raw(index), Vec2::new(x, y), HITBOX)
+    }
+
+    #[test]
+    fn nearest_target_on_the_line_first() {
+        let targets = [
+            target(0, 500.0, 100.0),
+            target(1, 200.0, 110.0),
+            target(2, 300.0, 100.0),
+            // Off the line.
+            target(3, 100.0, 200.0),
+            // Behind.
+            target(4, -100.0, 100.0),
+        ];
+        let hits = along(Vec2::new(0.0, 100.0), 1.0, 600.0, targets);
+        let order: Vec<u32> = hits.iter().map(|(entity, _)| entity.index()).collect();
+        assert_eq!(order, [1, 2, 0]);
+        assert_eq!(hits[0].1, 180.0);
+    }
+
+    #[test]
+    fn range_and_direction_limit_hits() {
+        let targets = [
+            target(0, -150.0, 0.0),
+            target(1, -400.0, 0.0),
+            target(2, 150.0, 0.0),
+        ];
+        let hits = along(Vec2::ZERO, -1.0, 300.0, targets);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0], (Entity::from_raw(0), 130.0));
+        // A target straddling the start is hit right away.
+        let hits = along(Vec2::ZERO, 1.0, 300.0, [target(5, 10.0, 0.0)]);
+        assert_eq!(hits, [(Entity::from_raw(5), 0.0)]);
+        assert!(along(Vec2::ZERO, 1.0, 100.0, [target(6, 150.0, 0.0)]).is_empty());
+    }
+
+    #[test]
+    fn beam_downs_only_the_first_enemy() {
+        let mut harness = Harness::new(WINDOW_SIZE);
+        harness.start(8);
+        harness.clear();
+        let (_, position) = harness.player().unwrap();
+        let near = harness.spawn_enemy(position + Vec3::X * 200.0, Lander, None);
+        let far = harness.spawn_enemy(position + Vec3::X * 400.0, Lander, None);
+        let beyond = harness.spawn_enemy(position + Vec3::X * 1000.0, Lander, None);
+        let now = harness.app.world.resource::<GameTime>().elapsed_seconds();
+        harness
+            .app
+            .world
+            .spawn(bundle(position + Vec3::X * 35.0, 1.0, false, now));
+        harness.step(2, Controls::default());
+        let world = &harness.app.world;
+        assert!(world.get_entity(near).is_none());
+        assert!(world.get_entity(far).is_some());
+        assert!(world.get_entity(beyond).is_some());
+
+        let closer = harness.spawn_enemy(position + Vec3::X * 150.0, Lander, None);
+        harness
+            .app
+            .world
+            .spawn(bundle(position + Vec3::X * 35.0, 1.0, true, now));
+        harness.step(2, Controls::default());
+        let world = &harness.app.world;
+        assert!(world.get_entity(closer).is_none());
+        assert!(world.get_entity(far).is_none());
+        // Out of the window.
+        assert!(world.get_entity(beyond).is_some());
+    }
+}
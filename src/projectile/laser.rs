@@ -9,6 +9,10 @@ use utils::bevy::hit::*;
 #[derive(Component)]
 pub struct Laser;
 
+/// Goes on through whatever it hits.
+#[derive(Component)]
+pub struct Piercing;
+
 pub const SPEED: f32 = 2400.0 * 2.0;
 
 impl MyTransform for Laser {
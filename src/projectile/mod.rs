@@ -1,13 +1,21 @@
-use bevy::{prelude::*, sprite::collide_aabb::collide};
+use bevy::{prelude::*, sprite::collide_aabb::collide, utils::HashSet};
 
 use crate::{
     assets::{GameAssets, MyTexture, MyTransform},
     map, style, utils,
 };
-use utils::bevy::{hit::*, projectile::Projectile, state::Simulation, window};
+use utils::bevy::{
+    fixed::{Interpolated, Step},
+    hit::*,
+    projectile::Projectile,
+    state::simulating,
+    window,
+};
 
+pub mod beam;
 pub mod laser;
 pub mod orb;
+pub mod pattern;
 
 #[derive(Bundle)]
 pub struct Bundle<T: Send + Sync + Component> {
@@ -15,6 +23,7 @@ pub struct Bundle<T: Send + Sync + Component> {
     sprite_bundle: SpriteBundle,
     scroll: map::Scroll,
     variant: T,
+    interpolated: Interpolated,
 }
 
 impl<T: Component + MyTexture + MyTransform> Bundle<T> {
@@ -38,6 +47,7 @@ impl<T: Component + MyTexture + MyTransform> Bundle<T> {
             },
             scroll: map::Scroll,
             variant,
+            interpolated: Interpolated::default(),
         }
     }
 }
@@ -46,18 +56,21 @@ pub struct Plug;
 
 impl Plugin for Plug {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
+        app.add_plugins((beam::Plug, pattern::Plug)).add_systems(
+            FixedUpdate,
             (
                 despawn_outside_window,
                 detect_hits::<laser::Laser>,
+                spend_lasers.after(detect_hits::<laser::Laser>),
                 detect_hits::<orb::Orb>,
             )
-                .run_if(in_state(Simulation::Running)),
+                .run_if(simulating)
+                .in_set(Step::Main),
         );
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn despawn_outside_window(
     query: Query<(Entity, &Transform), (With<Projectile>, Without<Camera>)>,
     camera_query: Query<&Transform, With<Camera>>,
@@ -65,13 +78,31 @@ fn despawn_outside_window(
     mut commands: Commands,
 ) {
     for (entity, transform) in query.iter() {
-        if let None = collide(
+        if collide(
             camera_query.single().translation,
             window_size.0,
             transform.translation,
             style::LASER_BOUND,
-        ) {
+        )
+        .is_none()
+        {
             commands.entity(entity).despawn();
         }
     }
 }
+
+/// Lasers end at the first thing they hit, unless they pierce.
+fn spend_lasers(
+    query: Query<&Hittable<laser::Laser>>,
+    spendable_query: Query<(), (With<laser::Laser>, Without<laser::Piercing>)>,
+    mut commands: Commands,
+) {
+    let spent: HashSet<Entity> = query
+        .iter()
+        .filter_map(|hittable| hittable.hit_entity)
+        .filter(|laser| spendable_query.contains(*laser))
+        .collect();
+    for laser in spent {
+        commands.entity(laser).despawn();
+    }
+}
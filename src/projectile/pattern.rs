@@ -0,0 +1,485 @@
+use super::{orb::Orb, Bundle};
+use crate::{
+    assets::{self, GameAssets, MyTransform},
+    camera, map,
+    player::{self, Player},
+    style, utils,
+};
+use bevy::prelude::*;
+use std::f32::consts::{PI, TAU};
+use utils::bevy::{
+    fixed::Step,
+    game_time::GameTime,
+    projectile::Projectile,
+    state::{simulating, GameState},
+    DespawnTime,
+};
+
+/// How far in front of the shooter orbs start.
+const MUZZLE: f32 = 50.0;
+
+/// How an enemy fires, every shot of it is an orb.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Pattern {
+    /// One orb at the target.
+    Aimed,
+    /// `count` orbs fanned out over `width` turns around the aim.
+    Spread { count: u32, width: f32 },
+    /// `count` aimed orbs, `interval` seconds apart.
+    Burst { count: u32, interval: f32 },
+    /// `count` orbs all around.
+    Ring { count: u32 },
+    /// A slower orb turning towards the player by up to `turn` turns a
+    /// second, gone after `lifetime` seconds.
+    Homing {
+        speed: f32,
+        turn: f32,
+        lifetime: f32,
+    },
+    /// A still orb bursting into a ring of `count` after `delay` seconds.
+    Mine { delay: f32, count: u32 },
+}
+
+/// Patterns by the name variants refer to them with.
+pub const LIBRARY: [(&str, Pattern); 6] = [
+    ("aimed", Pattern::Aimed),
+    (
+        "spread",
+        Pattern::Spread {
+            count: 3,
+            width: 0.08,
+        },
+    ),
+    (
+        "burst",
+        Pattern::Burst {
+            count: 3,
+            interval: 0.12,
+        },
+    ),
+    ("ring", Pattern::Ring { count: 8 }),
+    (
+        "homing",
+        Pattern::Homing {
+            speed: 0.4,
+            turn: 0.25,
+            lifetime: 4.0,
+        },
+    ),
+    (
+        "mine",
+        Pattern::Mine {
+            delay: 1.5,
+            count: 6,
+        },
+    ),
+];
+
+pub fn named(name: &str) -> Option<Pattern> {
+    LIBRARY
+        .iter()
+        .find(|(other, _)| *other == name)
+        .map(|(_, pattern)| *pattern)
+}
+
+/// Who, from where, which way and how fast a pattern is fired.
+#[derive(Clone, Copy)]
+pub struct Shot {
+    /// The rest of a burst is fired from wherever it is then, and dropped
+    /// once it's gone.
+    pub shooter: Entity,
+    pub origin: Vec3,
+    /// Turns counterclockwise from the right.
+    pub angle: f32,
+    pub speed: f32,
+    pub color: Color,
+}
+
+impl Shot {
+    fn orb(&self, assets: &GameAssets, angle: f32, speed: f32) -> Bundle<Orb> {
+        let position = self.origin + utils::bevy::clock(angle).extend(0.0) * MUZZLE;
+        Bundle::new(assets, position, angle, speed, self.color, Orb)
+    }
+}
+
+/// The rest of a burst.
+#[derive(Component)]
+pub struct Scheduled {
+    due: f32,
+    shot: Shot,
+}
+
+#[derive(Component)]
+pub struct Homing {
+    /// Turns a second.
+    pub turn: f32,
+}
+
+#[derive(Component)]
+pub struct Mine {
+    /// Game time it goes off at.
+    pub bursts_at: f32,
+    pub count: u32,
+    /// Of the orbs it bursts into.
+    pub speed: f32,
+}
+
+impl Pattern {
+    pub fn fire(&self, shot: &Shot, commands: &mut Commands, assets: &GameAssets, now: f32) {
+        match *self {
+            Pattern::Aimed => {
+                commands.spawn(shot.orb(assets, shot.angle, shot.speed));
+            }
+            Pattern::Spread { count, width } => {
+                for i in 0..count {
+                    let t = match count {
+                        1 => 0.0,
+                        _ => i as f32 / (count - 1) as f32 - 0.5,
+                    };
+                    commands.spawn(shot.orb(assets, shot.angle + t * width, shot.speed));
+                }
+            }
+            Pattern::Burst { count, interval } => {
+                commands.spawn(shot.orb(assets, shot.angle, shot.speed));
+                for i in 1..count {
+                    commands.spawn(Scheduled {
+                        due: now + interval * i as f32,
+                        shot: *shot,
+                    });
+                }
+            }
+            Pattern::Ring { count } => {
+                for i in 0..count {
+                    let angle = shot.angle + i as f32 / count as f32;
+                    commands.spawn(shot.orb(assets, angle, shot.speed));
+                }
+            }
+            Pattern::Homing {
+                speed,
+                turn,
+                lifetime,
+            } => {
+                commands.spawn((
+                    shot.orb(assets, shot.angle, shot.speed * speed),
+                    Homing { turn },
+                    DespawnTime {
+                        elapsed_seconds: now + lifetime,
+                    },
+                ));
+            }
+            Pattern::Mine { delay, count } => {
+                commands.spawn((
+                    Bundle::new(assets, shot.origin, 0.0, 0.0, shot.color, Orb),
+                    Mine {
+                        bursts_at: now + delay,
+                        count,
+                        speed: shot.speed,
+                    },
+                ));
+            }
+        }
+    }
+}
+
+pub struct Plug;
+
+impl Plugin for Plug {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (fire_scheduled, home, detonate)
+                .run_if(simulating)
+                .in_set(Step::Main),
+        );
+    }
+}
+
+fn fire_scheduled(
+    mut commands: Commands,
+    query: Query<(Entity, &Scheduled)>,
+    shooter_query: Query<&Transform>,
+    assets: Res<GameAssets>,
+    game_time: Res<GameTime>,
+) {
+    let now = game_time.elapsed_seconds();
+    for (entity, scheduled) in query.iter() {
+        let Ok(shooter) = shooter_query.get(scheduled.shot.shooter) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        if scheduled.due <= now {
+            let shot = Shot {
+                origin: shooter.translation,
+                ..scheduled.shot
+            };
+            Pattern::Aimed.fire(&shot, &mut commands, &assets, now);
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn home(
+    mut query: Query<(&mut Projectile, &mut Transform, &Homing)>,
+    player_query: Query<&Transform, (With<Player>, Without<Homing>)>,
+    time: Res<Time>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    for (mut projectile, mut transform, homing) in query.iter_mut() {
+        let velocity = projectile.velocity.truncate();
+        let to = (player.translation - transform.translation).truncate();
+        let current = velocity.y.atan2(velocity.x);
+        let off = (to.y.atan2(to.x) - current + PI).rem_euclid(TAU) - PI;
+        let step = (homing.turn * TAU * time.delta_seconds()).min(off.abs());
+        let angle = (current + step * off.signum()) / TAU;
+        projectile.velocity = (utils::bevy::clock(angle) * velocity.length()).extend(0.0);
+        transform.rotation = Orb::transform(angle).rotation;
+    }
+}
+
+fn detonate(
+    mut commands: Commands,
+    query: Query<(Entity, &Transform, &Sprite, &Mine)>,
+    assets: Res<GameAssets>,
+    game_time: Res<GameTime>,
+) {
+    let now = game_time.elapsed_seconds();
+    for (entity, transform, sprite, mine) in query.iter() {
+        if mine.bursts_at <= now {
+            let shot = Shot {
+                shooter: entity,
+                origin: transform.translation,
+                angle: 0.0,
+                speed: mine.speed,
+                color: sprite.color,
+            };
+            Pattern::Ring { count: mine.count }.fire(&shot, &mut commands, &assets, now);
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// How often the emitters of the debug scene fire.
+const SCENE_PERIOD: f32 = 2.0;
+const SCENE_SPACING: f32 = 180.0;
+const SCENE_HEIGHT: f32 = 560.0;
+const SCENE_TARGET: Vec3 = Vec3::new(0.0, 160.0, 0.0);
+
+/// Fires the pattern it's named after in the debug scene.
+#[derive(Component)]
+struct Emitter(Pattern);
+
+/// Every pattern of the library side by side, firing at a still ship, for
+/// tuning them.
+pub fn scene() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: "Defender patterns".into(),
+                    ..default()
+                }),
+                ..default()
+            }),
+            utils::bevy::Plug,
+            map::Plug,
+            super::Plug,
+        ))
+        .insert_resource(ClearColor(Color::BLACK))
+        .add_systems(Startup, (assets::load, camera::spawn))
+        .add_systems(PostStartup, spawn_scene)
+        .add_systems(Update, (camera::window_height_center, emit))
+        .run();
+}
+
+fn spawn_scene(mut commands: Commands, assets: Res<GameAssets>, asset_server: Res<AssetServer>) {
+    commands.insert_resource(NextState(Some(GameState::Playing)));
+    player::spawn(&mut commands, &assets, SCENE_TARGET);
+    let first = -(LIBRARY.len() as f32 - 1.0) * 0.5 * SCENE_SPACING;
+    for (i, (name, pattern)) in LIBRARY.iter().enumerate() {
+        let position = Vec3::new(first + i as f32 * SCENE_SPACING, SCENE_HEIGHT, 0.0);
+        commands.spawn((
+            SpriteBundle {
+                transform: Transform::from_translation(position)
+                    .with_scale(style::ENEMY_SCALE.extend(1.0)),
+                texture: assets.enemy_texture.clone(),
+                ..default()
+            },
+            Emitter(*pattern),
+        ));
+        commands.spawn(Text2dBundle {
+            text: Text::from_section(
+                *name,
+                TextStyle {
+                    font: asset_server.load(style::FONT),
+                    font_size: 20.0,
+                    color: style::SCORE_COLOR,
+                },
+            ),
+            transform: Transform::from_translation(position + Vec3::Y * 60.0),
+            ..default()
+        });
+    }
+}
+
+fn emit(
+    mut commands: Commands,
+    query: Query<(Entity, &Transform, &Emitter)>,
+    assets: Res<GameAssets>,
+    game_time: Res<GameTime>,
+    mut next: Local<f32>,
+) {
+    let now = game_time.elapsed_seconds();
+    if now < *next {
+        return;
+    }
+    *next = now + SCENE_PERIOD;
+    for (entity, transform, emitter) in query.iter() {
+        let to = SCENE_TARGET - transform.translation;
+        let shot = Shot {
+            shooter: entity,
+            origin: transform.translation,
+            angle: to.y.atan2(to.x) / TAU,
+            speed: super::orb::SPEED,
+            color: utils::bevy::bloom_hue(240.0),
+        };
+        emitter.0.fire(&shot, &mut commands, &assets, now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        enemy::{lander::Lander, mutant::Mutant, Variant},
+        harness::{Harness, WINDOW_SIZE},
+        player::input::Controls,
+    };
+    use bevy::ecs::system::CommandQueue;
+
+    fn fire(world: &mut World, pattern: Pattern, shooter: Entity, origin: Vec3, angle: f32) {
+        let shot = Shot {
+            shooter,
+            origin,
+            angle,
+            speed: crate::projectile::orb::SPEED,
+            color: Color::RED,
+        };
+        let now = world
+            .get_resource::<GameTime>()
+            .map_or(0.0, |game_time| game_time.elapsed_seconds());
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, world);
+        pattern.fire(&shot, &mut commands, &GameAssets::default(), now);
+        queue.apply(world);
+    }
+
+    /// Turns the orbs `pattern` fires at once head in, in order.
+    fn angles(pattern: Pattern, angle: f32) -> Vec<f32> {
+        let mut world = World::new();
+        let shooter = world.spawn_empty().id();
+        fire(&mut world, pattern, shooter, Vec3::ZERO, angle);
+        let mut angles: Vec<f32> = world
+            .query::<&Projectile>()
+            .iter(&world)
+            .map(|projectile| {
+                let velocity = projectile.velocity;
+                (velocity.y.atan2(velocity.x) / TAU).rem_euclid(1.0)
+            })
+            .collect();
+        angles.sort_by(f32::total_cmp);
+        angles
+    }
+
+    fn assert_turns(angles: Vec<f32>, expected: &[f32]) {
+        assert_eq!(angles.len(), expected.len(), "{:?}", angles);
+        for (angle, expected) in angles.iter().zip(expected) {
+            assert!((angle - expected).abs() < 1e-4, "{:?}", angles);
+        }
+    }
+
+    /// A run with a single enemy far away, so the wave goes on.
+    fn quiet_run() -> Harness {
+        let mut harness = Harness::new(WINDOW_SIZE);
+        harness.start(6);
+        harness.clear();
+        let far = Vec3::new(map::SIZE * 0.5, 300.0, 0.0);
+        harness.spawn_enemy(far, Lander, None);
+        harness
+    }
+
+    #[test]
+    fn spread_fans_out_around_the_aim() {
+        let spread = Pattern::Spread {
+            count: 3,
+            width: 0.1,
+        };
+        assert_turns(angles(spread, 0.25), &[0.2, 0.25, 0.3]);
+        let single = Pattern::Spread {
+            count: 1,
+            width: 0.1,
+        };
+        assert_turns(angles(single, 0.25), &[0.25]);
+    }
+
+    #[test]
+    fn ring_goes_all_around() {
+        let ring = angles(Pattern::Ring { count: 8 }, 0.0);
+        let expected: Vec<f32> = (0..8).map(|i| i as f32 / 8.0).collect();
+        assert_turns(ring, &expected);
+    }
+
+    #[test]
+    fn mine_bursts_on_time() {
+        let mut harness = quiet_run();
+        let (_, position) = harness.player().unwrap();
+        let origin = position + Vec3::new(400.0, 150.0, 0.0);
+        let (delay, count) = (0.5, 6);
+        let world = &mut harness.app.world;
+        let shooter = world.spawn_empty().id();
+        fire(world, Pattern::Mine { delay, count }, shooter, origin, 0.0);
+        let bursts_at = world.query::<&Mine>().single(world).bursts_at;
+        assert_eq!(harness.count::<Orb>(), 1);
+
+        let burst = harness.run_until(delay * 2.0, Controls::default(), |harness| {
+            harness.count::<Mine>() == 0
+        });
+        assert!(burst);
+        let now = harness.app.world.resource::<GameTime>().elapsed_seconds();
+        assert!(
+            now >= bursts_at && now < bursts_at + 0.1,
+            "{} {}",
+            now,
+            bursts_at
+        );
+        assert_eq!(harness.count::<Orb>(), count as usize);
+    }
+
+    #[test]
+    fn burst_stops_with_its_shooter() {
+        let mut harness = quiet_run();
+        let (_, position) = harness.player().unwrap();
+        let origin = position + Vec3::new(400.0, 150.0, 0.0);
+        let burst = Pattern::Burst {
+            count: 3,
+            interval: 0.1,
+        };
+        let shooter = harness.spawn_enemy(origin, Mutant, None);
+        fire(&mut harness.app.world, burst, shooter, origin, 0.5);
+        assert_eq!(harness.count::<Scheduled>(), 2);
+        harness.app.world.despawn(shooter);
+        harness.step(16, Controls::default());
+        assert_eq!(harness.count::<Scheduled>(), 0);
+        assert_eq!(harness.count::<Orb>(), 1);
+    }
+
+    #[test]
+    fn variants_fire_known_patterns() {
+        for data in [Lander::data(), Mutant::data()] {
+            assert!(named(data.pattern).is_some(), "{}", data.pattern);
+        }
+        assert_eq!(named("aimed"), Some(Pattern::Aimed));
+        assert_eq!(named("nothing"), None);
+    }
+}
@@ -0,0 +1,432 @@
+use crate::{
+    attract,
+    difficulty::{Challenge, Difficulty},
+    game_over::GameOver,
+    new_game::NewGame,
+    player::{input::Controls, physics::Arcade},
+    settings::Settings,
+    utils,
+};
+use bevy::{app::AppExit, prelude::*, time::TimeUpdateStrategy};
+use std::time::Duration;
+use utils::bevy::{state::GameState, window};
+
+const MAGIC: &[u8; 4] = b"DFRP";
+const VERSION: u8 = 2;
+
+#[derive(Clone, Copy)]
+struct Frame {
+    delta: Duration,
+    /// Off while the player has the game paused.
+    running: bool,
+    controls: Controls,
+}
+
+/// Settings a run was played with that change how it plays out, taken over
+/// from the tape instead of settings.json when playing it back.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Rules {
+    difficulty: Difficulty,
+    adaptive: bool,
+    /// `Challenge::streak` when the run started.
+    streak: u32,
+    beam: bool,
+    arcade: bool,
+    physics: Arcade,
+    mouse_control: bool,
+}
+
+impl Rules {
+    fn new(settings: &Settings, challenge: &Challenge) -> Self {
+        Self {
+            difficulty: settings.difficulty,
+            adaptive: settings.adaptive,
+            streak: challenge.streak,
+            beam: settings.beam,
+            arcade: settings.arcade,
+            physics: settings.physics,
+            mouse_control: settings.mouse_control,
+        }
+    }
+}
+
+/// Everything needed to replay a run: its seed, the window and settings it
+/// was played with and the controls and time step of every frame since it
+/// started.
+#[derive(Clone)]
+pub struct Tape {
+    seed: u64,
+    window_size: Vec2,
+    rules: Rules,
+    frames: Vec<Frame>,
+}
+
+pub enum Error {
+    Io(std::io::Error),
+    Format(&'static str),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "io: {}", error),
+            Error::Format(error) => write!(f, "format: {}", error),
+        }
+    }
+}
+
+const RUNNING: u8 = 1 << 0;
+const MOVE_UP: u8 = 1 << 1;
+const MOVE_DOWN: u8 = 1 << 2;
+const MOVE_LEFT: u8 = 1 << 3;
+const MOVE_RIGHT: u8 = 1 << 4;
+const SHOOT: u8 = 1 << 5;
+const RESCUE: u8 = 1 << 6;
+const EXTRA: u8 = 1 << 7;
+
+const ANALOG: u8 = 1 << 0;
+const TARGET_Y: u8 = 1 << 1;
+
+const ADAPTIVE: u8 = 1 << 0;
+const BEAM: u8 = 1 << 1;
+const ARCADE: u8 = 1 << 2;
+const MOUSE_CONTROL: u8 = 1 << 3;
+
+impl Tape {
+    fn new(seed: u64, window_size: Vec2, rules: Rules) -> Self {
+        Self {
+            seed,
+            window_size,
+            rules,
+            frames: vec![],
+        }
+    }
+
+    /// Overrides the settings that change how the run plays out with the
+    /// ones it was recorded with.
+    pub fn apply(&self, settings: &mut Settings) {
+        let rules = &self.rules;
+        settings.difficulty = rules.difficulty;
+        settings.adaptive = rules.adaptive;
+        settings.beam = rules.beam;
+        settings.arcade = rules.arcade;
+        settings.physics = rules.physics;
+        settings.mouse_control = rules.mouse_control;
+    }
+
+    pub fn load(path: &str) -> Result<Self, Error> {
+        let bytes = std::fs::read(path).map_err(Error::Io)?;
+        Self::read(&bytes)
+    }
+
+    fn save(&self, path: &str) -> Result<(), Error> {
+        std::fs::write(path, self.write()).map_err(Error::Io)
+    }
+
+    fn write(&self) -> Vec<u8> {
+        let mut output = vec![];
+        output.extend(MAGIC);
+        output.push(VERSION);
+        output.extend(self.seed.to_le_bytes());
+        output.extend(self.window_size.x.to_le_bytes());
+        output.extend(self.window_size.y.to_le_bytes());
+        let rules = &self.rules;
+        let difficulty = Difficulty::ALL.iter().position(|d| *d == rules.difficulty);
+        output.push(difficulty.unwrap() as u8);
+        let flags = [
+            (rules.adaptive, ADAPTIVE),
+            (rules.beam, BEAM),
+            (rules.arcade, ARCADE),
+            (rules.mouse_control, MOUSE_CONTROL),
+        ];
+        output.push(
+            flags
+                .iter()
+                .filter(|(on, _)| *on)
+                .fold(0, |a, (_, b)| a | b),
+        );
+        output.extend(rules.streak.to_le_bytes());
+        let physics = &rules.physics;
+        for value in [
+            physics.thrust,
+            physics.brake,
+            physics.drag,
+            physics.climb,
+            physics.bank,
+        ] {
+            output.extend(value.to_le_bytes());
+        }
+        for frame in &self.frames {
+            let nanos = frame.delta.as_nanos().min(u32::MAX as u128) as u32;
+            output.extend(nanos.to_le_bytes());
+            let controls = &frame.controls;
+            let mut extra = 0;
+            if controls.analog != Vec2::ZERO {
+                extra |= ANALOG;
+            }
+            if controls.target_y.is_some() {
+                extra |= TARGET_Y;
+            }
+            let flags = [
+                (frame.running, RUNNING),
+                (controls.move_up, MOVE_UP),
+                (controls.move_down, MOVE_DOWN),
+                (controls.move_left, MOVE_LEFT),
+                (controls.move_right, MOVE_RIGHT),
+                (controls.shoot, SHOOT),
+                (controls.rescue, RESCUE),
+                (extra != 0, EXTRA),
+            ];
+            output.push(
+                flags
+                    .iter()
+                    .filter(|(on, _)| *on)
+                    .fold(0, |a, (_, b)| a | b),
+            );
+            if extra != 0 {
+                output.push(extra);
+            }
+            if extra & ANALOG != 0 {
+                output.extend(controls.analog.x.to_le_bytes());
+                output.extend(controls.analog.y.to_le_bytes());
+            }
+            if let Some(target_y) = controls.target_y {
+                output.extend(target_y.to_le_bytes());
+            }
+        }
+        output
+    }
+
+    fn read(mut input: &[u8]) -> Result<Self, Error> {
+        let mut take = |n: usize| {
+            if input.len() < n {
+                return Err(Error::Format("unexpected end"));
+            }
+            let (head, tail) = input.split_at(n);
+            input = tail;
+            Ok(head)
+        };
+        if take(4)? != MAGIC {
+            return Err(Error::Format("not a replay"));
+        }
+        if take(1)?[0] != VERSION {
+            return Err(Error::Format("unsupported version"));
+        }
+        let u32 = |bytes: &[u8]| u32::from_le_bytes(bytes.try_into().unwrap());
+        let f32 = |bytes: &[u8]| f32::from_le_bytes(bytes.try_into().unwrap());
+        let seed = u64::from_le_bytes(take(8)?.try_into().unwrap());
+        let window_size = Vec2::new(f32(take(4)?), f32(take(4)?));
+        let difficulty = *Difficulty::ALL
+            .get(take(1)?[0] as usize)
+            .ok_or(Error::Format("unknown difficulty"))?;
+        let flags = take(1)?[0];
+        let streak = u32(take(4)?);
+        let physics = Arcade {
+            thrust: f32(take(4)?),
+            brake: f32(take(4)?),
+            drag: f32(take(4)?),
+            climb: f32(take(4)?),
+            bank: f32(take(4)?),
+        };
+        let rules = Rules {
+            difficulty,
+            adaptive: flags & ADAPTIVE != 0,
+            streak,
+            beam: flags & BEAM != 0,
+            arcade: flags & ARCADE != 0,
+            physics,
+            mouse_control: flags & MOUSE_CONTROL != 0,
+        };
+        let mut tape = Self::new(seed, window_size, rules);
+        while let Ok(nanos) = take(4) {
+            let delta = Duration::from_nanos(u32(nanos) as u64);
+            let flags = take(1)?[0];
+            let extra = if flags & EXTRA != 0 { take(1)?[0] } else { 0 };
+            let analog = if extra & ANALOG != 0 {
+                Vec2::new(f32(take(4)?), f32(take(4)?))
+            } else {
+                Vec2::ZERO
+            };
+            let target_y = if extra & TARGET_Y != 0 {
+                Some(f32(take(4)?))
+            } else {
+                None
+            };
+            tape.frames.push(Frame {
+                delta,
+                running: flags & RUNNING != 0,
+                controls: Controls {
+                    move_up: flags & MOVE_UP != 0,
+                    move_down: flags & MOVE_DOWN != 0,
+                    move_left: flags & MOVE_LEFT != 0,
+                    move_right: flags & MOVE_RIGHT != 0,
+                    shoot: flags & SHOOT != 0,
+                    rescue: flags & RESCUE != 0,
+                    analog,
+                    target_y,
+                },
+            });
+        }
+        Ok(tape)
+    }
+}
+
+#[derive(Resource, Default)]
+pub enum Replay {
+    #[default]
+    Off,
+    Record {
+        path: String,
+        tape: Option<Tape>,
+    },
+    Play {
+        tape: Tape,
+        index: usize,
+    },
+}
+
+pub struct Plug {
+    pub record: Option<String>,
+    pub play: Option<Tape>,
+}
+
+impl Plugin for Plug {
+    fn build(&self, app: &mut App) {
+        let replay = match (&self.record, &self.play) {
+            (_, Some(tape)) => Replay::Play {
+                tape: tape.clone(),
+                index: 0,
+            },
+            (Some(path), None) => Replay::Record {
+                path: path.clone(),
+                tape: None,
+            },
+            (None, None) => Replay::Off,
+        };
+        app.insert_resource(replay)
+            .add_systems(PreUpdate, feed.run_if(playing))
+            .add_systems(PostUpdate, (start_recording, stop_recording))
+            .add_systems(Last, (record_frame, next_delta.run_if(playing)));
+    }
+}
+
+pub fn playing(replay: Res<Replay>) -> bool {
+    matches!(*replay, Replay::Play { .. })
+}
+
+pub fn live(replay: Res<Replay>) -> bool {
+    !playing(replay)
+}
+
+fn start_recording(
+    mut events: EventReader<NewGame>,
+    mut replay: ResMut<Replay>,
+    window_size: Res<window::Size>,
+    settings: Option<Res<Settings>>,
+    challenge: Res<Challenge>,
+    demo: Option<Res<attract::Demo>>,
+) {
+    for new_game in events.read() {
+        if demo.is_some() {
+            continue;
+        }
+        if let Replay::Record { tape, .. } = &mut *replay {
+            let settings = settings.as_deref().cloned().unwrap_or_default();
+            let rules = Rules::new(&settings, &challenge);
+            *tape = Some(Tape::new(new_game.seed, window_size.0, rules));
+        }
+    }
+}
+
+fn record_frame(
+    mut replay: ResMut<Replay>,
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    controls: Res<Controls>,
+) {
+    if let Replay::Record {
+        tape: Some(tape), ..
+    } = &mut *replay
+    {
+        tape.frames.push(Frame {
+            delta: time.delta(),
+            running: *state.get() != GameState::Paused,
+            controls: *controls,
+        });
+    }
+}
+
+/// Saves the tape once the run is over, lost or left for the title or the
+/// desktop.
+fn stop_recording(
+    mut game_over: EventReader<GameOver>,
+    mut exit: EventReader<AppExit>,
+    state: Res<State<GameState>>,
+    mut replay: ResMut<Replay>,
+) {
+    let events = game_over.read().count() + exit.read().count();
+    let to_title = state.is_changed() && *state.get() == GameState::Title;
+    if events == 0 && !to_title {
+        return;
+    }
+    if let Replay::Record { path, tape } = &mut *replay {
+        if let Some(tape) = tape.take() {
+            match tape.save(path) {
+                Ok(()) => info!("saved replay of {} frames to {}", tape.frames.len(), path),
+                Err(error) => error!("saving replay to {}: {}", path, error),
+            }
+        }
+    }
+}
+
+/// Stands in for `input::input` and the pause key while playing back.
+fn feed(
+    mut replay: ResMut<Replay>,
+    mut controls: ResMut<Controls>,
+    mut new_game: EventWriter<NewGame>,
+    mut challenge: ResMut<Challenge>,
+    mut commands: Commands,
+    window_size: Res<window::Size>,
+    state: Res<State<GameState>>,
+) {
+    let Replay::Play { tape, index } = &mut *replay else {
+        return;
+    };
+    if *index == 0 {
+        if tape.window_size != window_size.0 {
+            warn!(
+                "replay recorded at {:?}, playing at {:?}, it may diverge",
+                tape.window_size, window_size.0
+            );
+        }
+        challenge.streak = tape.rules.streak;
+        new_game.send(NewGame { seed: tape.seed });
+    } else if let Some(frame) = tape.frames.get(*index) {
+        *controls = frame.controls;
+        let next_state = match (frame.running, state.get()) {
+            (false, GameState::Playing) => Some(GameState::Paused),
+            (true, GameState::Paused) => Some(GameState::Playing),
+            _ => None,
+        };
+        if let Some(next_state) = next_state {
+            commands.insert_resource(NextState(Some(next_state)));
+        }
+    } else {
+        info!("replay finished after {} frames", index);
+        commands.insert_resource(TimeUpdateStrategy::Automatic);
+        if *state.get() == GameState::Playing {
+            commands.insert_resource(NextState(Some(GameState::Paused)));
+        }
+        *replay = Replay::Off;
+        return;
+    }
+    *index += 1;
+}
+
+fn next_delta(replay: Res<Replay>, mut commands: Commands) {
+    if let Replay::Play { tape, index } = &*replay {
+        if let Some(frame) = tape.frames.get(*index) {
+            commands.insert_resource(TimeUpdateStrategy::ManualDuration(frame.delta));
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Source of all gameplay randomness, reseeded at the start of every run so
+/// the run can be reproduced from its seed.
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u64,
+    /// Seed given on the command line, used for every run instead of the time.
+    fixed_seed: Option<u64>,
+    rng: StdRng,
+}
+
+impl GameRng {
+    pub fn new(fixed_seed: Option<u64>) -> Self {
+        let seed = fixed_seed.unwrap_or(0);
+        Self {
+            seed,
+            fixed_seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn next_seed(&self) -> u64 {
+        self.fixed_seed.unwrap_or_else(time_seed)
+    }
+
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    pub fn f32(&mut self) -> f32 {
+        self.rng.gen()
+    }
+}
+
+/// Randomness that never affects gameplay, like particles and colors, kept
+/// apart so drawing from it doesn't shift the [`GameRng`] sequence.
+#[derive(Resource)]
+pub struct CosmeticRng(StdRng);
+
+impl CosmeticRng {
+    pub fn f32(&mut self) -> f32 {
+        self.0.gen()
+    }
+
+    pub fn u32(&mut self) -> u32 {
+        self.0.gen()
+    }
+}
+
+fn time_seed() -> u64 {
+    #[cfg(not(target_family = "wasm"))]
+    let seed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_nanos() as u64);
+    #[cfg(target_family = "wasm")]
+    let seed = rand::random();
+    seed
+}
+
+pub struct Plug {
+    pub seed: Option<u64>,
+}
+
+impl Plugin for Plug {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameRng::new(self.seed))
+            .insert_resource(CosmeticRng(StdRng::seed_from_u64(time_seed())));
+    }
+}
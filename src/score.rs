@@ -1,6 +1,6 @@
 use bevy::{prelude::*, window::PrimaryWindow};
 
-use crate::style;
+use crate::{style, utils};
 use std::f32::consts::TAU;
 
 #[derive(Resource)]
@@ -8,6 +8,23 @@ pub struct Score {
     pub value: u32,
 }
 
+/// What the player did during the current wave, for the summary after it.
+#[derive(Resource, Default)]
+pub struct WaveStats {
+    pub shots: u32,
+    pub hits: u32,
+    pub destroyed: u32,
+}
+
+impl WaveStats {
+    pub fn accuracy(&self) -> f32 {
+        match self.shots {
+            0 => 0.0,
+            shots => self.hits as f32 / shots as f32,
+        }
+    }
+}
+
 #[derive(Component)]
 struct ScoreText;
 
@@ -16,13 +33,14 @@ pub struct Plug;
 impl Plugin for Plug {
     fn build(&self, app: &mut App) {
         app.insert_resource(Score { value: 0 })
-            .add_systems(Startup, spawn_score_text)
-            .add_systems(Update, update_score_text);
+            .init_resource::<WaveStats>();
+        if !utils::bevy::headless(app) {
+            app.add_systems(Startup, spawn_score_text)
+                .add_systems(Update, update_score_text);
+        }
     }
 }
 
-const TEXT_SPACE: f32 = 0.5 * (1.0 - style::MINIMAP_SIZE.x);
-
 fn spawn_score_text(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
@@ -39,7 +57,7 @@ fn spawn_score_text(
         .with_style(Style {
             position_type: PositionType::Absolute,
             top: Val::Px((window.height() * style::MINIMAP_SIZE.y - font_size) * 0.5),
-            right: Val::Px(window.width() * (1.0 - TEXT_SPACE) + 15.0),
+            right: Val::Px(window.width() * (1.0 - style::TEXT_SPACE) + 15.0),
             ..default()
         }),
         ScoreText,
@@ -59,7 +77,7 @@ fn update_score_text(
     let window = window_query.single();
     let font_size = style::SCORE_FONT_SIZE;
     style.top = Val::Px((window.height() * style::MINIMAP_SIZE.y - font_size) * 0.5);
-    style.right = Val::Px(window.width() * (1.0 - TEXT_SPACE) + 15.0);
+    style.right = Val::Px(window.width() * (1.0 - style::TEXT_SPACE) + 15.0);
     text.sections[0].value = format!("{:06}", score.value);
     text.sections[0].style.color = Color::Hsla {
         hue: (time.elapsed_seconds() / 6.0 * HZ).fract() * 360.0,
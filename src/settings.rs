@@ -0,0 +1,468 @@
+use crate::{
+    audio::Bus, difficulty::Difficulty, menu::PausedMenu, navigation, player::physics::Arcade,
+    style, utils,
+};
+use bevy::{
+    core_pipeline::bloom::BloomSettings, prelude::*, ui::RelativeCursorPosition, window::WindowMode,
+};
+use serde::{Deserialize, Serialize};
+use utils::bevy::state::GameState;
+
+const PATH: &str = "assets/settings.json";
+const MSAA_SAMPLES: [u32; 4] = [1, 2, 4, 8];
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub effects_volume: f32,
+    pub voice_volume: f32,
+    pub music_volume: f32,
+    pub fullscreen: bool,
+    pub bloom: bool,
+    /// Samples per pixel, 1 turns MSAA off.
+    pub msaa: u32,
+    pub screen_shake: f32,
+    /// Used from the next run on.
+    pub difficulty: Difficulty,
+    /// Eases off after runs lost in a row.
+    pub adaptive: bool,
+    /// Fires the classic beam instead of laser bolts.
+    pub beam: bool,
+    /// Flies with momentum on both axes.
+    pub arcade: bool,
+    /// Tuning of the arcade flight model. Not on the settings screen, only
+    /// changed by editing settings.json.
+    pub physics: Arcade,
+    /// Steers the ship with the cursor, mouse buttons bound as any other.
+    pub mouse_control: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 0.5,
+            effects_volume: 0.4,
+            voice_volume: 0.6,
+            music_volume: 0.5,
+            fullscreen: true,
+            bloom: true,
+            msaa: 4,
+            screen_shake: 1.0,
+            difficulty: Difficulty::Normal,
+            adaptive: false,
+            beam: false,
+            arcade: false,
+            physics: Arcade::default(),
+            mouse_control: false,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        #[cfg(not(target_family = "wasm"))]
+        if let Ok(input) = std::fs::read_to_string(PATH) {
+            match serde_json::from_str(&input) {
+                Ok(settings) => return settings,
+                Err(error) => warn!("invalid settings, using defaults: {}", error),
+            }
+        }
+        Self::default()
+    }
+
+    pub fn save(&self) {
+        #[cfg(not(target_family = "wasm"))]
+        if let Ok(output) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::write(PATH, output);
+        }
+    }
+
+    /// Volume of everything played on `bus`, the engine follows effects.
+    pub fn bus(&self, bus: Bus) -> f32 {
+        self.master_volume
+            * match bus {
+                Bus::Sfx | Bus::Engine => self.effects_volume,
+                Bus::Voice => self.voice_volume,
+                Bus::Music => self.music_volume,
+            }
+    }
+
+    pub fn window_mode(&self) -> WindowMode {
+        match self.fullscreen {
+            true => WindowMode::BorderlessFullscreen,
+            false => WindowMode::Windowed,
+        }
+    }
+
+    pub fn msaa(&self) -> Msaa {
+        match self.msaa {
+            1 => Msaa::Off,
+            2 => Msaa::Sample2,
+            8 => Msaa::Sample8,
+            _ => Msaa::Sample4,
+        }
+    }
+}
+
+/// A line of the settings screen.
+#[derive(Component, Clone, Copy)]
+enum Setting {
+    MasterVolume,
+    EffectsVolume,
+    VoiceVolume,
+    MusicVolume,
+    ScreenShake,
+    WindowMode,
+    Bloom,
+    Msaa,
+    Difficulty,
+    Adaptive,
+    Beam,
+    Physics,
+    MouseControl,
+    Back,
+}
+
+impl Setting {
+    const SLIDERS: [Setting; 5] = [
+        Setting::MasterVolume,
+        Setting::EffectsVolume,
+        Setting::VoiceVolume,
+        Setting::MusicVolume,
+        Setting::ScreenShake,
+    ];
+    const TOGGLES: [Setting; 9] = [
+        Setting::WindowMode,
+        Setting::Bloom,
+        Setting::Msaa,
+        Setting::Difficulty,
+        Setting::Adaptive,
+        Setting::Beam,
+        Setting::Physics,
+        Setting::MouseControl,
+        Setting::Back,
+    ];
+
+    fn level(&self, settings: &Settings) -> f32 {
+        match self {
+            Setting::MasterVolume => settings.master_volume,
+            Setting::EffectsVolume => settings.effects_volume,
+            Setting::VoiceVolume => settings.voice_volume,
+            Setting::MusicVolume => settings.music_volume,
+            Setting::ScreenShake => settings.screen_shake,
+            _ => 0.0,
+        }
+    }
+
+    fn set_level(&self, settings: &mut Settings, level: f32) {
+        match self {
+            Setting::MasterVolume => settings.master_volume = level,
+            Setting::EffectsVolume => settings.effects_volume = level,
+            Setting::VoiceVolume => settings.voice_volume = level,
+            Setting::MusicVolume => settings.music_volume = level,
+            Setting::ScreenShake => settings.screen_shake = level,
+            _ => {}
+        }
+    }
+
+    fn label(&self, settings: &Settings) -> String {
+        let on_off = |on| if on { "on" } else { "off" };
+        match self {
+            Setting::MasterVolume => "master volume".to_string(),
+            Setting::EffectsVolume => "effects volume".to_string(),
+            Setting::VoiceVolume => "voice volume".to_string(),
+            Setting::MusicVolume => "music volume".to_string(),
+            Setting::ScreenShake => "screen shake".to_string(),
+            Setting::WindowMode => match settings.fullscreen {
+                true => "fullscreen".to_string(),
+                false => "windowed".to_string(),
+            },
+            Setting::Bloom => format!("bloom {}", on_off(settings.bloom)),
+            Setting::Msaa => match settings.msaa {
+                1 => "msaa off".to_string(),
+                samples => format!("msaa {}x", samples),
+            },
+            Setting::Difficulty => format!("difficulty {}", settings.difficulty.name()),
+            Setting::Adaptive => format!("adaptive {}", on_off(settings.adaptive)),
+            Setting::Beam => match settings.beam {
+                true => "laser beam".to_string(),
+                false => "laser bolts".to_string(),
+            },
+            Setting::Physics => match settings.arcade {
+                true => "arcade physics".to_string(),
+                false => "classic physics".to_string(),
+            },
+            Setting::MouseControl => format!("mouse control {}", on_off(settings.mouse_control)),
+            Setting::Back => "back".to_string(),
+        }
+    }
+
+    fn press(&self, settings: &mut Settings) {
+        match self {
+            Setting::WindowMode => settings.fullscreen = !settings.fullscreen,
+            Setting::Bloom => settings.bloom = !settings.bloom,
+            Setting::Msaa => {
+                let i = MSAA_SAMPLES.iter().position(|s| *s == settings.msaa);
+                let next = i.map_or(0, |i| (i + 1) % MSAA_SAMPLES.len());
+                settings.msaa = MSAA_SAMPLES[next];
+            }
+            Setting::Difficulty => settings.difficulty = settings.difficulty.next(),
+            Setting::Adaptive => settings.adaptive = !settings.adaptive,
+            Setting::Beam => settings.beam = !settings.beam,
+            Setting::Physics => settings.arcade = !settings.arcade,
+            Setting::MouseControl => settings.mouse_control = !settings.mouse_control,
+            _ => {}
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct SettingsMenu;
+
+#[derive(Component)]
+struct SettingLabel(Setting);
+
+#[derive(Component)]
+struct SliderFill(Setting);
+
+pub struct Plug {
+    pub settings: Settings,
+}
+
+impl Plugin for Plug {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.settings.clone())
+            .add_systems(Startup, spawn)
+            .add_systems(
+                Update,
+                (
+                    drag_slider,
+                    nudge_slider,
+                    press_button,
+                    update_labels,
+                    apply,
+                ),
+            );
+        for state in [GameState::Title, GameState::Paused] {
+            app.add_systems(OnExit(state), hide);
+        }
+    }
+}
+
+/// Passes display settings on to the window and the camera, each only once
+/// it changed, so a volume change doesn't reset the window.
+fn apply(
+    settings: Res<Settings>,
+    mut applied: Local<Option<Settings>>,
+    mut commands: Commands,
+    mut window_query: Query<&mut Window>,
+    camera_query: Query<(Entity, Option<&BloomSettings>), With<Camera>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let old = applied.replace(settings.clone());
+    let old = old.as_ref();
+    if old.is_none_or(|old| old.fullscreen != settings.fullscreen) {
+        for mut window in window_query.iter_mut() {
+            window.mode = settings.window_mode();
+        }
+    }
+    if old.is_none_or(|old| old.msaa != settings.msaa) {
+        commands.insert_resource(settings.msaa());
+    }
+    for (entity, bloom) in camera_query.iter() {
+        match (settings.bloom, bloom) {
+            (true, None) => {
+                commands.entity(entity).insert(BloomSettings::default());
+            }
+            (false, Some(_)) => {
+                commands.entity(entity).remove::<BloomSettings>();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn drag_slider(
+    query: Query<(&Interaction, &RelativeCursorPosition, &Setting)>,
+    mut settings: ResMut<Settings>,
+) {
+    for (interaction, cursor, setting) in query.iter() {
+        let Some(position) = cursor.normalized else {
+            continue;
+        };
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let level = position.x.clamp(0.0, 1.0);
+        if setting.level(&settings) != level {
+            setting.set_level(&mut settings, level);
+        }
+    }
+}
+
+fn nudge_slider(
+    mut nudges: EventReader<navigation::Nudge>,
+    query: Query<&Setting>,
+    mut settings: ResMut<Settings>,
+) {
+    for nudge in nudges.read() {
+        if let Ok(setting) = query.get(nudge.entity) {
+            let level = (setting.level(&settings) + nudge.amount).clamp(0.0, 1.0);
+            if setting.level(&settings) != level {
+                setting.set_level(&mut settings, level);
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn press_button(
+    query: Query<(&Interaction, &Setting), (Changed<Interaction>, Without<RelativeCursorPosition>)>,
+    mut settings: ResMut<Settings>,
+    mut menu_query: Query<&mut Style, (With<PausedMenu>, Without<SettingsMenu>)>,
+    mut settings_query: Query<&mut Style, With<SettingsMenu>>,
+) {
+    for (interaction, setting) in query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match setting {
+            Setting::Back => {
+                settings.save();
+                settings_query.single_mut().display = Display::None;
+                menu_query.single_mut().display = Display::Flex;
+            }
+            setting => setting.press(&mut settings),
+        }
+    }
+}
+
+fn update_labels(
+    settings: Res<Settings>,
+    mut label_query: Query<(&mut Text, &SettingLabel)>,
+    mut fill_query: Query<(&mut Style, &SliderFill)>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (mut text, label) in label_query.iter_mut() {
+        text.sections[0].value = label.0.label(&settings);
+    }
+    for (mut style, fill) in fill_query.iter_mut() {
+        style.width = Val::Percent(fill.0.level(&settings) * 100.0);
+    }
+}
+
+fn hide(mut query: Query<&mut Style, With<SettingsMenu>>, settings: Res<Settings>) {
+    let mut style = query.single_mut();
+    if style.display != Display::None {
+        settings.save();
+        style.display = Display::None;
+    }
+}
+
+fn spawn(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let minimap_size = style::MINIMAP_SIZE * 100.0;
+    let pad = 0.125 * 600.0 * 0.125;
+    let text_style = |font_size| TextStyle {
+        font: asset_server.load(style::FONT),
+        font_size,
+        color: utils::bevy::grey(1.0, 1.0),
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    display: Display::None,
+                    width: Val::Percent(50.0),
+                    height: Val::Percent(100.0 - minimap_size.y),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(pad),
+                    top: Val::Percent(minimap_size.y),
+                    left: Val::Percent((100.0 - minimap_size.x) * 0.5),
+                    padding: UiRect::all(Val::Px(pad)),
+                    border: UiRect::horizontal(Val::Px(1.0)),
+                    ..default()
+                },
+                border_color: utils::bevy::grey(1.0, 0.5).into(),
+                ..default()
+            },
+            SettingsMenu,
+        ))
+        .with_children(|parent| {
+            for setting in Setting::SLIDERS {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            justify_content: JustifyContent::SpaceBetween,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn((
+                            TextBundle::from_section("", text_style(24.0)),
+                            SettingLabel(setting),
+                        ));
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        width: Val::Percent(50.0),
+                                        height: Val::Px(24.0),
+                                        ..default()
+                                    },
+                                    background_color: utils::bevy::grey(1.0 / 16.0, 0.9).into(),
+                                    ..default()
+                                },
+                                RelativeCursorPosition::default(),
+                                navigation::Slider,
+                                setting,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    NodeBundle {
+                                        style: Style {
+                                            height: Val::Percent(100.0),
+                                            ..default()
+                                        },
+                                        background_color: style::MENU_OPTION_COLOR.into(),
+                                        ..default()
+                                    },
+                                    SliderFill(setting),
+                                ));
+                            });
+                    });
+            }
+            for setting in Setting::TOGGLES {
+                let color = match setting {
+                    Setting::Back => style::MENU_EXIT_COLOR,
+                    _ => style::MENU_OPTION_COLOR,
+                };
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                padding: UiRect::all(Val::Px(5.0)),
+                                ..default()
+                            },
+                            background_color: color.with_a(0.5).into(),
+                            ..default()
+                        },
+                        setting,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            TextBundle::from_section("", text_style(32.0)),
+                            SettingLabel(setting),
+                        ));
+                    });
+            }
+        });
+}
@@ -0,0 +1,639 @@
+use crate::{
+    assets::GameAssets,
+    difficulty::{Challenge, Difficulty},
+    enemy::{self, wave::Pending, wave::Reinforcements, EnemiesCount, Enemy, VariantData},
+    map::terrain::Terrain,
+    person::{self, CharacterState, Person},
+    player::{self, power_up, Player},
+    projectile::{
+        self,
+        laser::Laser,
+        orb::Orb,
+        pattern::{Homing, Mine, Scheduled},
+    },
+    rng::GameRng,
+    score::{Score, WaveStats},
+    utils,
+};
+use bevy::{ecs::system::CommandQueue, prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+use utils::{
+    bevy::{game_time::GameTime, projectile::Projectile, state::GameState, DespawnTime},
+    Side,
+};
+
+/// Bumped whenever the layout changes incompatibly, snapshots of other
+/// versions are refused rather than half read. Fields added with
+/// `#[serde(default)]` keep the version, older snapshots read as defaults.
+const VERSION: u32 = 1;
+const PATH: &str = "assets/run.json";
+
+/// An in-progress run, enough to pick it up again where it was left.
+/// Timers are kept as seconds from the moment of the snapshot. The gameplay
+/// RNG is not kept, restoring reseeds it from `seed` and so restarts its
+/// stream: a resumed run goes on differently from how it would have.
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    version: u32,
+    seed: u64,
+    terrain_seed: u32,
+    #[serde(default)]
+    difficulty: Difficulty,
+    camera_x: f32,
+    score: u32,
+    enemies_count: u32,
+    wave: u32,
+    /// Trickled enemies still to come, due in seconds from the snapshot.
+    #[serde(default)]
+    pending: Vec<Pending>,
+    player: PlayerState,
+    enemies: Vec<EnemyState>,
+    people: Vec<PersonState>,
+    projectiles: Vec<ProjectileState>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PlayerState {
+    position: Vec3,
+    facing: Side,
+    horizontal_speed: f32,
+    #[serde(default)]
+    vertical_speed: f32,
+    next_shot: f32,
+    /// Seconds left of each power-up the ship has.
+    #[serde(default)]
+    power_ups: Vec<(power_up::Kind, f32)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EnemyState {
+    variant: String,
+    position: Vec3,
+    desired_position: Vec3,
+    next_shot: f32,
+    next_desired_position: f32,
+    last_outside: f32,
+    /// Index into `people` of the humanoid carried.
+    person: Option<usize>,
+}
+
+/// Who holds a humanoid, by index instead of by entity, which doesn't
+/// survive the round trip.
+#[derive(Serialize, Deserialize)]
+enum Captor {
+    Player,
+    Enemy(usize),
+}
+
+#[derive(Serialize, Deserialize)]
+enum Character {
+    CapturedBy(Captor, Vec2),
+    Falling,
+    Grounded,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PersonState {
+    position: Vec2,
+    state: Character,
+}
+
+#[derive(Serialize, Deserialize)]
+enum Kind {
+    Laser,
+    Orb,
+    Homing {
+        turn: f32,
+        lifetime: f32,
+    },
+    Mine {
+        bursts_in: f32,
+        count: u32,
+        speed: f32,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProjectileState {
+    kind: Kind,
+    position: Vec3,
+    velocity: Vec3,
+    color: Color,
+}
+
+/// Read first, to refuse other versions before reading the rest.
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Version(u32),
+    Variant(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "io: {}", error),
+            Error::Json(error) => write!(f, "json: {}", error),
+            Error::Version(version) => write!(f, "unsupported version {}", version),
+            Error::Variant(name) => write!(f, "unknown enemy {}", name),
+        }
+    }
+}
+
+impl Snapshot {
+    /// Takes the run in `world`, none if the player isn't alive.
+    pub fn capture(world: &mut World) -> Option<Self> {
+        let now = world.resource::<GameTime>().elapsed_seconds();
+        let (player_entity, transform, player, active) = world
+            .query::<(Entity, &Transform, &Player, &power_up::Active)>()
+            .get_single(world)
+            .ok()?;
+        let player_state = PlayerState {
+            position: transform.translation,
+            facing: player.facing,
+            horizontal_speed: player.horizontal_speed,
+            vertical_speed: player.vertical_speed,
+            next_shot: player.next_shot_time - now,
+            power_ups: power_up::Kind::ALL
+                .into_iter()
+                .filter(|kind| active.on(*kind, now))
+                .map(|kind| (kind, active.left(kind, now)))
+                .collect(),
+        };
+
+        let enemies: Vec<_> = world
+            .query::<(Entity, &Transform, &Enemy, &VariantData)>()
+            .iter(world)
+            .map(|(entity, transform, enemy, variant)| {
+                let state = EnemyState {
+                    variant: variant.name.to_string(),
+                    position: transform.translation,
+                    desired_position: enemy.desired_position,
+                    next_shot: enemy.next_shot - now,
+                    next_desired_position: enemy.next_desired_position - now,
+                    last_outside: enemy.last_outside - now,
+                    person: None,
+                };
+                (entity, enemy.person, state)
+            })
+            .collect();
+        let people: Vec<_> = world
+            .query_filtered::<(Entity, &Transform, &CharacterState), With<Person>>()
+            .iter(world)
+            .map(|(entity, transform, state)| (entity, transform.translation.truncate(), *state))
+            .collect();
+        let enemy_index: HashMap<Entity, usize> = enemies
+            .iter()
+            .enumerate()
+            .map(|(i, (entity, ..))| (*entity, i))
+            .collect();
+        let person_index: HashMap<Entity, usize> = people
+            .iter()
+            .enumerate()
+            .map(|(i, (entity, ..))| (*entity, i))
+            .collect();
+
+        let enemies = enemies
+            .into_iter()
+            .map(|(_, person, state)| EnemyState {
+                person: person.and_then(|person| person_index.get(&person).copied()),
+                ..state
+            })
+            .collect();
+        let people = people
+            .into_iter()
+            .map(|(_, position, state)| {
+                let state = match state {
+                    CharacterState::CapturedBy(captor, offset) => {
+                        let captor = match enemy_index.get(&captor) {
+                            Some(i) => Some(Captor::Enemy(*i)),
+                            None if captor == player_entity => Some(Captor::Player),
+                            None => None,
+                        };
+                        match captor {
+                            Some(captor) => Character::CapturedBy(captor, offset),
+                            None => Character::Falling,
+                        }
+                    }
+                    CharacterState::Falling => Character::Falling,
+                    CharacterState::Grounded => Character::Grounded,
+                };
+                PersonState { position, state }
+            })
+            .collect();
+
+        let mut laser_query = world.query_filtered::<(), With<Laser>>();
+        let mut projectile_query = world.query::<(
+            Entity,
+            &Transform,
+            &Projectile,
+            &Sprite,
+            Option<&Homing>,
+            Option<&DespawnTime>,
+            Option<&Mine>,
+        )>();
+        let projectiles = projectile_query
+            .iter(world)
+            .map(
+                |(entity, transform, projectile, sprite, homing, despawn, mine)| ProjectileState {
+                    kind: match (laser_query.get(world, entity), homing, despawn, mine) {
+                        (Ok(()), ..) => Kind::Laser,
+                        (_, Some(homing), Some(despawn), _) => Kind::Homing {
+                            turn: homing.turn,
+                            lifetime: despawn.elapsed_seconds - now,
+                        },
+                        (.., Some(mine)) => Kind::Mine {
+                            bursts_in: mine.bursts_at - now,
+                            count: mine.count,
+                            speed: mine.speed,
+                        },
+                        _ => Kind::Orb,
+                    },
+                    position: transform.translation,
+                    velocity: projectile.velocity,
+                    color: sprite.color,
+                },
+            )
+            .collect();
+
+        let camera_x = world
+            .query_filtered::<&Transform, With<Camera>>()
+            .single(world)
+            .translation
+            .x;
+        let enemies_count = world.resource::<EnemiesCount>();
+        Some(Self {
+            version: VERSION,
+            seed: world.resource::<GameRng>().seed(),
+            terrain_seed: world.resource::<Terrain>().seed,
+            difficulty: world.resource::<Challenge>().difficulty,
+            camera_x,
+            score: world.resource::<Score>().value,
+            enemies_count: enemies_count.count,
+            pending: world
+                .resource::<Reinforcements>()
+                .pending
+                .iter()
+                .map(|pending| Pending {
+                    due: pending.due - now,
+                    ..pending.clone()
+                })
+                .collect(),
+            wave: enemies_count.wave,
+            player: player_state,
+            enemies,
+            people,
+            projectiles,
+        })
+    }
+
+    /// Replaces whatever run is in `world` with this one, paused.
+    pub fn restore(&self, world: &mut World) -> Result<(), Error> {
+        let variants = self.enemies.iter().map(|enemy| &enemy.variant);
+        let pending = self.pending.iter().map(|pending| &pending.variant);
+        if let Some(name) = variants
+            .chain(pending)
+            .find(|name| !enemy::is_variant(name))
+        {
+            return Err(Error::Variant(name.clone()));
+        }
+        let leftovers: Vec<Entity> = world
+            .query_filtered::<Entity, Or<(
+                With<Enemy>,
+                With<Person>,
+                With<Projectile>,
+                With<Player>,
+                With<Scheduled>,
+                With<power_up::Pickup>,
+                With<projectile::beam::Beam>,
+            )>>()
+            .iter(world)
+            .collect();
+        for entity in leftovers {
+            world.despawn(entity);
+        }
+        if world.resource::<Terrain>().seed != self.terrain_seed {
+            world.insert_resource(Terrain::new(self.terrain_seed));
+        }
+        world
+            .query_filtered::<&mut Transform, With<Camera>>()
+            .single_mut(world)
+            .translation
+            .x = self.camera_x;
+
+        let now = world.resource::<GameTime>().elapsed_seconds();
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, world);
+        let assets = world.resource::<GameAssets>();
+        let player = player::spawn(&mut commands, assets, self.player.position);
+        // Reserved up front so references can point to entities spawned later.
+        let enemies: Vec<Entity> = self
+            .enemies
+            .iter()
+            .map(|_| commands.spawn_empty().id())
+            .collect();
+        let people: Vec<Entity> = self
+            .people
+            .iter()
+            .map(|_| commands.spawn_empty().id())
+            .collect();
+        for (state, entity) in self.enemies.iter().zip(&enemies) {
+            let person = state.person.map(|i| people[i]);
+            let mut entity = commands.entity(*entity);
+            enemy::insert_variant(&mut entity, &state.variant, state.position, person, assets);
+        }
+        for (state, entity) in self.people.iter().zip(&people) {
+            let character = match &state.state {
+                Character::CapturedBy(Captor::Player, offset) => {
+                    CharacterState::CapturedBy(player, *offset)
+                }
+                Character::CapturedBy(Captor::Enemy(i), offset) => {
+                    CharacterState::CapturedBy(enemies[*i], *offset)
+                }
+                Character::Falling => CharacterState::Falling,
+                Character::Grounded => CharacterState::Grounded,
+            };
+            commands
+                .entity(*entity)
+                .insert(person::bundle(state.position, character, assets));
+        }
+        for state in &self.projectiles {
+            let velocity = state.velocity.truncate();
+            let angle = velocity.y.atan2(velocity.x) / TAU;
+            let speed = velocity.length();
+            let (position, color) = (state.position, state.color);
+            let mut entity = match state.kind {
+                Kind::Laser => commands.spawn(projectile::Bundle::new(
+                    assets, position, angle, speed, color, Laser,
+                )),
+                _ => commands.spawn(projectile::Bundle::new(
+                    assets, position, angle, speed, color, Orb,
+                )),
+            };
+            match state.kind {
+                Kind::Homing { turn, lifetime } => {
+                    entity.insert((
+                        Homing { turn },
+                        DespawnTime {
+                            elapsed_seconds: now + lifetime,
+                        },
+                    ));
+                }
+                Kind::Mine {
+                    bursts_in,
+                    count,
+                    speed,
+                } => {
+                    entity.insert(Mine {
+                        bursts_at: now + bursts_in,
+                        count,
+                        speed,
+                    });
+                }
+                Kind::Laser | Kind::Orb => {}
+            }
+        }
+        queue.apply(world);
+
+        if let Some(mut transform) = world.get_mut::<Transform>(player) {
+            if self.player.facing == Side::Left {
+                transform.rotate(utils::bevy::angle(0.5));
+            }
+        }
+        if let Some(mut player) = world.get_mut::<Player>(player) {
+            player.facing = self.player.facing;
+            player.horizontal_speed = self.player.horizontal_speed;
+            player.vertical_speed = self.player.vertical_speed;
+            player.next_shot_time = now + self.player.next_shot;
+        }
+        if let Some(mut active) = world.get_mut::<power_up::Active>(player) {
+            for (kind, left) in &self.player.power_ups {
+                active.set_left(*kind, now, *left);
+            }
+        }
+        for (state, entity) in self.enemies.iter().zip(&enemies) {
+            if let Some(mut enemy) = world.get_mut::<Enemy>(*entity) {
+                enemy.desired_position = state.desired_position;
+                enemy.next_shot = now + state.next_shot;
+                enemy.next_desired_position = now + state.next_desired_position;
+                enemy.last_outside = now + state.last_outside;
+            }
+        }
+
+        // Restarts the stream, see `Snapshot`.
+        world.resource_mut::<GameRng>().reseed(self.seed);
+        world.resource_mut::<Challenge>().difficulty = self.difficulty;
+        world.resource_mut::<Score>().value = self.score;
+        *world.resource_mut::<EnemiesCount>() = EnemiesCount {
+            count: self.enemies_count,
+            wave: self.wave,
+        };
+        world.resource_mut::<Reinforcements>().pending = self
+            .pending
+            .iter()
+            .map(|pending| Pending {
+                due: now + pending.due,
+                ..pending.clone()
+            })
+            .collect();
+        *world.resource_mut::<WaveStats>() = WaveStats::default();
+        world.insert_resource(NextState(Some(GameState::Paused)));
+        Ok(())
+    }
+
+    pub fn load(path: &str) -> Result<Self, Error> {
+        let input = std::fs::read_to_string(path).map_err(Error::Io)?;
+        Self::read(&input)
+    }
+
+    fn save(&self, path: &str) -> Result<(), Error> {
+        std::fs::write(path, self.write()?).map_err(Error::Io)
+    }
+
+    fn write(&self) -> Result<String, Error> {
+        serde_json::to_string_pretty(self).map_err(Error::Json)
+    }
+
+    fn read(input: &str) -> Result<Self, Error> {
+        let header: Header = serde_json::from_str(input).map_err(Error::Json)?;
+        if header.version != VERSION {
+            return Err(Error::Version(header.version));
+        }
+        serde_json::from_str(input).map_err(Error::Json)
+    }
+}
+
+/// The run left when quitting, offered to continue from the title.
+#[derive(Resource, Default)]
+pub struct Saved {
+    pub run: Option<Snapshot>,
+}
+
+/// Keeps the run in progress for later.
+#[derive(Event)]
+pub struct Save;
+
+/// Picks up the saved run.
+#[derive(Event)]
+pub struct Resume;
+
+pub struct Plug;
+
+impl Plugin for Plug {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Saved>()
+            .add_event::<Save>()
+            .add_event::<Resume>()
+            .add_systems(Startup, load)
+            .add_systems(PostUpdate, (save, resume));
+    }
+}
+
+fn load(mut saved: ResMut<Saved>) {
+    #[cfg(not(target_family = "wasm"))]
+    match Snapshot::load(PATH) {
+        Ok(run) => saved.run = Some(run),
+        Err(Error::Io(_)) => {}
+        Err(error) => warn!("ignoring saved run: {}", error),
+    }
+}
+
+fn save(world: &mut World) {
+    if world.resource_mut::<Events<Save>>().drain().count() == 0 {
+        return;
+    }
+    let Some(run) = Snapshot::capture(world) else {
+        return;
+    };
+    #[cfg(not(target_family = "wasm"))]
+    if let Err(error) = run.save(PATH) {
+        error!("saving run to {}: {}", PATH, error);
+    }
+    world.resource_mut::<Saved>().run = Some(run);
+}
+
+/// Continues the saved run, which is used up. The file stays if it can't be.
+fn resume(world: &mut World) {
+    if world.resource_mut::<Events<Resume>>().drain().count() == 0 {
+        return;
+    }
+    let Some(run) = world.resource_mut::<Saved>().run.take() else {
+        return;
+    };
+    match run.restore(world) {
+        Ok(()) => {
+            #[cfg(not(target_family = "wasm"))]
+            let _ = std::fs::remove_file(PATH);
+        }
+        Err(error) => error!("resuming run: {}", error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        enemy::{lander::Lander, mutant::Mutant},
+        harness::{Harness, WINDOW_SIZE},
+        player::input::Controls,
+    };
+
+    fn captor(harness: &Harness, person: Entity) -> Option<Entity> {
+        match harness.person_state(person) {
+            Some(CharacterState::CapturedBy(captor, _)) => Some(*captor),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn round_trip_remaps_captors() {
+        let mut harness = Harness::new(WINDOW_SIZE);
+        harness.start(3);
+        harness.clear();
+        let (player, position) = harness.player().unwrap();
+        let far = Vec3::new(crate::map::SIZE * 0.5, 300.0, 0.0);
+        let carried = harness.spawn_person(far.truncate(), CharacterState::Grounded);
+        let lander = harness.spawn_enemy(far, Lander, Some(carried));
+        *harness
+            .app
+            .world
+            .get_mut::<CharacterState>(carried)
+            .unwrap() = CharacterState::CapturedBy(lander, person::ENEMY_OFFSET);
+        harness.spawn_person(
+            position.truncate(),
+            CharacterState::CapturedBy(player, person::PLAYER_OFFSET),
+        );
+        harness.spawn_enemy(far + Vec3::X * 200.0, Mutant, None);
+        harness.app.world.spawn(projectile::Bundle::new(
+            &GameAssets::default(),
+            position + Vec3::new(200.0, 150.0, 0.0),
+            0.0,
+            projectile::orb::SPEED,
+            Color::RED,
+            Orb,
+        ));
+        harness.app.world.spawn((
+            projectile::Bundle::new(
+                &GameAssets::default(),
+                position + Vec3::new(-200.0, 150.0, 0.0),
+                0.0,
+                0.0,
+                Color::RED,
+                Orb,
+            ),
+            Mine {
+                bursts_at: 60.0,
+                count: 6,
+                speed: projectile::orb::SPEED,
+            },
+        ));
+        harness.app.world.resource_mut::<Score>().value = 4321;
+
+        let output = Snapshot::capture(&mut harness.app.world)
+            .unwrap()
+            .write()
+            .unwrap();
+        let snapshot = Snapshot::read(&output).unwrap();
+        harness.start(9);
+        snapshot.restore(&mut harness.app.world).unwrap();
+        harness.step(1, Controls::default());
+
+        assert_eq!(harness.state(), GameState::Paused);
+        assert_eq!(harness.score(), 4321);
+        assert_eq!(harness.count::<Lander>(), 1);
+        assert_eq!(harness.count::<Mutant>(), 1);
+        assert_eq!(harness.count::<Person>(), 2);
+        assert_eq!(harness.count::<Orb>(), 2);
+        assert_eq!(harness.count::<Mine>(), 1);
+        let count = harness.app.world.resource::<EnemiesCount>().count;
+        assert_eq!(count, 2);
+
+        let world = &mut harness.app.world;
+        let (lander, carried) = world
+            .query_filtered::<(Entity, &Enemy), With<Lander>>()
+            .single(world);
+        let carried = carried.person.unwrap();
+        assert_eq!(captor(&harness, carried), Some(lander));
+        let (player, _) = harness.player().unwrap();
+        let world = &mut harness.app.world;
+        let rescued = world
+            .query_filtered::<Entity, With<Person>>()
+            .iter(world)
+            .find(|entity| *entity != carried)
+            .unwrap();
+        assert_eq!(captor(&harness, rescued), Some(player));
+    }
+
+    #[test]
+    fn refuses_other_versions() {
+        let input = r#"{"version": 0}"#;
+        assert!(matches!(Snapshot::read(input), Err(Error::Version(0))));
+    }
+}
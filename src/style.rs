@@ -1,22 +1,31 @@
 use bevy::prelude::*;
 
 pub const FONT: &str = "fonts/Kenney Rocket.ttf";
-pub const VOLUME: f32 = 0.2;
-pub const VOICE_VOLUME: f32 = 0.3;
-pub const EXPLOSION_VOLUME: f32 = 0.3;
 
 pub const MENU_PLAY_COLOR: Color = Color::rgb(0.0, 0.75, 0.0);
 pub const MENU_EXIT_COLOR: Color = Color::rgb(1.0, 0.0, 0.0);
+pub const MENU_OPTION_COLOR: Color = Color::rgb(0.0, 0.25, 0.75);
+pub const MENU_FOCUS_COLOR: Color = Color::rgb(1.0, 0.85, 0.0);
 
 pub const TERRAIN_COLOR: Color = Color::ORANGE_RED;
 pub const BORDER_CONFINEMENT_OFFSET: f32 = 50.0;
 
 pub const BEGIN_SOUND: &str = "audio/begin.ogg";
 pub const GAME_OVER_SOUND: &str = "audio/game_over.ogg";
+/// Music loops are not bundled, the game goes without music unless all three
+/// are there. The gameplay and intense tracks should be the same length to
+/// stay in sync.
+pub const TITLE_MUSIC: &str = "audio/music/title.ogg";
+pub const GAMEPLAY_MUSIC: &str = "audio/music/gameplay.ogg";
+pub const INTENSE_MUSIC: &str = "audio/music/intense.ogg";
 pub const MIN_ENEMY_COUNT: u32 = 5;
 pub const MAX_ENEMY_COUNT: u32 = 15;
+pub const HUMANOID_BONUS: u32 = 50;
+pub const WAVE_BONUS: u32 = 10;
 
 pub const MINIMAP_SIZE: Vec2 = Vec2::new(0.5, 0.125);
+/// Part of the window width on each side of the minimap, for the HUD text.
+pub const TEXT_SPACE: f32 = 0.5 * (1.0 - MINIMAP_SIZE.x);
 pub const SCORE_FONT_SIZE: f32 = 60.0;
 pub const SCORE_COLOR: Color = Color::WHITE;
 pub const MINIMAP_COLOR: Color = Color::CYAN;
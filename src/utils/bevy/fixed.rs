@@ -0,0 +1,80 @@
+use bevy::{app::RunFixedUpdateLoop, prelude::*, time::run_fixed_update_schedule};
+
+pub const TICK_RATE: f64 = 64.0;
+
+/// Moves further than this in one tick are teleports, like wrapping around
+/// the map, and are not smoothed.
+const TELEPORT_DISTANCE: f32 = 1000.0;
+
+/// Order of gameplay inside one fixed tick.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Step {
+    Main,
+    Late,
+}
+
+/// Rendered between its last two ticks instead of jumping from tick to tick.
+#[derive(Component, Default)]
+pub struct Interpolated {
+    previous: Vec3,
+    current: Vec3,
+    ticked: bool,
+}
+
+pub struct Plug;
+
+impl Plugin for Plug {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(FixedUpdate, (Step::Main, Step::Late).chain())
+            .add_systems(
+                RunFixedUpdateLoop,
+                restore.before(run_fixed_update_schedule),
+            )
+            .add_systems(
+                FixedUpdate,
+                (begin_tick.before(Step::Main), end_tick.after(Step::Late)),
+            )
+            .add_systems(Update, interpolate);
+    }
+}
+
+/// Puts the simulated positions back before ticking.
+fn restore(mut query: Query<(&mut Transform, &Interpolated)>) {
+    for (mut transform, interpolated) in query.iter_mut() {
+        if interpolated.ticked {
+            transform.translation = interpolated.current;
+        }
+    }
+}
+
+fn begin_tick(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in query.iter_mut() {
+        interpolated.previous = transform.translation;
+    }
+}
+
+fn end_tick(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in query.iter_mut() {
+        // Spawned during this tick, so it has no earlier position.
+        if !interpolated.ticked {
+            interpolated.previous = transform.translation;
+        }
+        interpolated.current = transform.translation;
+        interpolated.ticked = true;
+    }
+}
+
+pub fn interpolate(mut query: Query<(&mut Transform, &Interpolated)>, time: Res<Time<Fixed>>) {
+    let t = time.overstep_percentage();
+    for (mut transform, interpolated) in query.iter_mut() {
+        if !interpolated.ticked {
+            continue;
+        }
+        let (previous, current) = (interpolated.previous, interpolated.current);
+        transform.translation = if previous.distance(current) > TELEPORT_DISTANCE {
+            current
+        } else {
+            previous.lerp(current, t)
+        };
+    }
+}
@@ -0,0 +1,69 @@
+use super::{fixed::Step, state::simulating};
+use bevy::prelude::*;
+use std::time::Duration;
+
+/// Time as seen by gameplay. Only moves on while the world is simulated, so
+/// pausing doesn't bring every timer due at once.
+#[derive(Resource, Default)]
+pub struct GameTime {
+    elapsed: Duration,
+}
+
+impl GameTime {
+    pub fn elapsed_seconds(&self) -> f32 {
+        self.elapsed.as_secs_f32()
+    }
+}
+
+pub struct Plug;
+
+impl Plugin for Plug {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameTime>()
+            .add_systems(FixedUpdate, tick.run_if(simulating).before(Step::Main));
+    }
+}
+
+fn tick(mut game_time: ResMut<GameTime>, time: Res<Time>) {
+    game_time.elapsed += time.delta();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::bevy::{fixed, state, state::GameState, try_despawning, DespawnTime};
+    use bevy::time::TimeUpdateStrategy;
+
+    const FRAME: Duration = Duration::from_millis(100);
+
+    fn run(app: &mut App, state: GameState, seconds: u32) {
+        app.insert_resource(NextState(Some(state)));
+        for _ in 0..seconds * 10 {
+            app.update();
+        }
+    }
+
+    #[test]
+    fn pausing_stops_gameplay_timers() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, state::Plug, fixed::Plug, Plug))
+            .add_systems(PostUpdate, try_despawning)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME));
+        run(&mut app, GameState::Playing, 2);
+        let before = app.world.resource::<GameTime>().elapsed_seconds();
+        assert!(before > 1.0);
+        let entity = app
+            .world
+            .spawn(DespawnTime {
+                elapsed_seconds: before + 1.0,
+            })
+            .id();
+
+        run(&mut app, GameState::Paused, 5);
+        assert_eq!(app.world.resource::<GameTime>().elapsed_seconds(), before);
+        assert!(app.world.get_entity(entity).is_some());
+
+        run(&mut app, GameState::Playing, 2);
+        assert!(app.world.get_entity(entity).is_none());
+    }
+}
@@ -5,6 +5,8 @@ use bevy::{
 
 use std::f32::consts::TAU;
 
+pub mod fixed;
+pub mod game_time;
 pub mod hit;
 pub mod projectile;
 pub mod state;
@@ -36,13 +38,28 @@ pub fn clock(ratio: f32) -> Vec2 {
     Vec2::new(scale.cos(), scale.sin())
 }
 
+/// Inserted before the plugins in apps that simulate without a window, they
+/// leave out whatever is drawn or played then.
+#[derive(Resource)]
+pub struct Headless;
+
+pub fn headless(app: &App) -> bool {
+    app.world.contains_resource::<Headless>()
+}
+
 pub struct Plug;
 
 impl Plugin for Plug {
     fn build(&self, app: &mut App) {
-        app.add_plugins((state::Plug, projectile::Plug, window::Plug))
-            .add_systems(PreUpdate, try_despawning)
-            .add_systems(PostUpdate, (follow, try_despawning));
+        app.add_plugins((
+            state::Plug,
+            fixed::Plug,
+            game_time::Plug,
+            projectile::Plug,
+            window::Plug,
+        ))
+        .add_systems(PreUpdate, try_despawning)
+        .add_systems(PostUpdate, (follow, try_despawning));
     }
 }
 
@@ -64,12 +81,17 @@ fn follow(
     }
 }
 
+/// Despawned once `GameTime` reaches `elapsed_seconds`.
 #[derive(Component)]
 pub struct DespawnTime {
     pub elapsed_seconds: f32,
 }
 
-fn try_despawning(mut commands: Commands, query: Query<(Entity, &DespawnTime)>, time: Res<Time>) {
+fn try_despawning(
+    mut commands: Commands,
+    query: Query<(Entity, &DespawnTime)>,
+    time: Res<game_time::GameTime>,
+) {
     for (entity, despawn_time) in query.iter() {
         if time.elapsed_seconds() >= despawn_time.elapsed_seconds {
             commands.entity(entity).despawn_recursive();
@@ -1,4 +1,4 @@
-use super::state::Simulation;
+use super::{fixed::Step, state::simulating};
 use bevy::prelude::*;
 
 #[derive(Component)]
@@ -16,6 +16,6 @@ pub struct Plug;
 
 impl Plugin for Plug {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, movement.run_if(in_state(Simulation::Running)));
+        app.add_systems(FixedUpdate, movement.run_if(simulating).in_set(Step::Main));
     }
 }
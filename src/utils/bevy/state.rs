@@ -1,32 +1,75 @@
 use bevy::prelude::*;
 
 #[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
-pub enum Simulation {
-    Running,
+pub enum GameState {
     #[default]
+    Title,
+    Playing,
     Paused,
+    WaveIntro,
+    PlayerDeath,
+    GameOver,
+    HighScoreEntry,
+}
+
+impl GameState {
+    pub const ALL: [GameState; 7] = [
+        GameState::Title,
+        GameState::Playing,
+        GameState::Paused,
+        GameState::WaveIntro,
+        GameState::PlayerDeath,
+        GameState::GameOver,
+        GameState::HighScoreEntry,
+    ];
+
+    /// Whether the world moves on in this state. It keeps going for a moment
+    /// after the player dies so the explosion plays out.
+    pub fn simulating(self) -> bool {
+        matches!(self, GameState::Playing | GameState::PlayerDeath)
+    }
+}
+
+pub fn simulating(state: Res<State<GameState>>) -> bool {
+    state.get().simulating()
+}
+
+/// Moves to `next` once `timer` finishes, unless the state is left before.
+#[derive(Resource)]
+pub struct Countdown {
+    pub next: GameState,
+    pub timer: Timer,
+}
+
+impl Countdown {
+    pub fn new(seconds: f32, next: GameState) -> Self {
+        Self {
+            next,
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+        }
+    }
 }
 
 pub struct Plug;
 
 impl Plugin for Plug {
     fn build(&self, app: &mut App) {
-        app
-            .add_state::<Simulation>()
-            .add_systems(Update, toggle_simulation_state);
+        app.add_state::<GameState>()
+            .add_systems(Update, count_down.run_if(resource_exists::<Countdown>()));
+        for state in GameState::ALL {
+            app.add_systems(OnEnter(state), move || debug!("entered {:?}", state))
+                .add_systems(OnExit(state), cancel_countdown);
+        }
     }
 }
 
-fn toggle_simulation_state(
-    key: Res<Input<KeyCode>>,
-    mut commands: Commands,
-    state: Res<State<Simulation>>
-) {
-    if key.just_pressed(KeyCode::Escape) {
-        let next_state = match state.get() {
-            Simulation::Running => Simulation::Paused,
-            Simulation::Paused => Simulation::Running,
-        };
-        commands.insert_resource(NextState(Some(next_state)));
+fn count_down(mut commands: Commands, mut countdown: ResMut<Countdown>, time: Res<Time>) {
+    if countdown.timer.tick(time.delta()).just_finished() {
+        commands.insert_resource(NextState(Some(countdown.next)));
+        commands.remove_resource::<Countdown>();
     }
-}
\ No newline at end of file
+}
+
+fn cancel_countdown(mut commands: Commands) {
+    commands.remove_resource::<Countdown>();
+}
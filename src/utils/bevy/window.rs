@@ -11,8 +11,12 @@ impl Plugin for Plug {
     }
 }
 
+/// Follows the primary window. Without one the size is left to whoever made
+/// the app.
 fn size_resource(mut commands: Commands, window_query: Query<&Window, With<PrimaryWindow>>) {
-    let window = window_query.single();
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
     commands.insert_resource(Size(Vec2 {
         x: window.width(),
         y: window.height(),
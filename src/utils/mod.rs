@@ -1,7 +1,9 @@
 pub mod bevy;
 pub mod range;
 
-#[derive(PartialEq, Clone, Copy)]
+use serde::{Deserialize, Serialize};
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub enum Side {
     Left,
     Right,
@@ -0,0 +1,173 @@
+use crate::{
+    enemy::{self, EnemiesCount},
+    person::Person,
+    player::input::Controls,
+    score::{Score, WaveStats},
+    style, utils,
+};
+use bevy::prelude::*;
+use utils::bevy::state::GameState;
+
+/// How long the numbers of the summary take to count up.
+const COUNT_TIME: f32 = 1.5;
+const SUMMARY_TIME: f32 = 3.5;
+const BANNER_TIME: f32 = 1.5;
+
+/// How the last wave went.
+struct Summary {
+    saved: u32,
+    destroyed: u32,
+    accuracy: f32,
+    wave_bonus: u32,
+}
+
+#[derive(Resource)]
+struct Intro {
+    summary: Option<Summary>,
+    elapsed: f32,
+    /// Set once shoot is released, so holding it through the end of a wave
+    /// doesn't skip the summary.
+    armed: bool,
+}
+
+#[derive(Component)]
+struct IntroText;
+
+pub struct Plug;
+
+impl Plugin for Plug {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::WaveIntro),
+            summarize.before(enemy::spawn_enemies),
+        )
+        .add_systems(Update, advance.run_if(in_state(GameState::WaveIntro)));
+        if !utils::bevy::headless(app) {
+            app.add_systems(Startup, spawn_text)
+                .add_systems(OnExit(GameState::WaveIntro), hide_text)
+                .add_systems(
+                    Update,
+                    show_text
+                        .after(advance)
+                        .run_if(in_state(GameState::WaveIntro)),
+                );
+        }
+    }
+}
+
+/// Pays the bonus for the wave that just ended, if any.
+fn summarize(
+    mut commands: Commands,
+    enemies: Res<EnemiesCount>,
+    script: Res<enemy::wave::Script>,
+    person_query: Query<(), With<Person>>,
+    mut score: ResMut<Score>,
+    mut stats: ResMut<WaveStats>,
+) {
+    let summary = (enemies.wave > 0).then(|| Summary {
+        saved: person_query.iter().count() as u32,
+        destroyed: stats.destroyed,
+        accuracy: stats.accuracy(),
+        wave_bonus: script.wave(&enemies).bonus(&enemies),
+    });
+    if let Some(summary) = &summary {
+        score.value += summary.wave_bonus + summary.saved * style::HUMANOID_BONUS;
+    }
+    *stats = WaveStats::default();
+    commands.insert_resource(Intro {
+        summary,
+        elapsed: 0.0,
+        armed: false,
+    });
+}
+
+impl Intro {
+    fn summary_time(&self) -> f32 {
+        match self.summary {
+            Some(_) => SUMMARY_TIME,
+            None => 0.0,
+        }
+    }
+}
+
+fn advance(
+    mut commands: Commands,
+    mut intro: ResMut<Intro>,
+    time: Res<Time>,
+    controls: Res<Controls>,
+) {
+    intro.elapsed += time.delta_seconds();
+    let skipped = intro.armed && controls.shoot;
+    intro.armed |= !controls.shoot;
+    if skipped || intro.elapsed >= intro.summary_time() + BANNER_TIME {
+        commands.insert_resource(NextState(Some(GameState::Playing)));
+    }
+}
+
+fn show_text(
+    intro: Res<Intro>,
+    enemies: Res<EnemiesCount>,
+    mut query: Query<(&mut Text, &mut Style), With<IntroText>>,
+) {
+    let Ok((mut text, mut style)) = query.get_single_mut() else {
+        return;
+    };
+    let summary_time = intro.summary_time();
+    style.display = Display::Flex;
+    text.sections[0].value = match &intro.summary {
+        Some(summary) if intro.elapsed < summary_time => {
+            let t = (intro.elapsed / COUNT_TIME).min(1.0);
+            let count = |value: u32| (value as f32 * t).round() as u32;
+            format!(
+                "HUMANOIDS SAVED {}\nBONUS PER HUMANOID {}\nENEMIES DESTROYED {}\nACCURACY {}%\nWAVE BONUS {}",
+                count(summary.saved),
+                count(style::HUMANOID_BONUS),
+                count(summary.destroyed),
+                count((summary.accuracy * 100.0).round() as u32),
+                count(summary.wave_bonus),
+            )
+        }
+        _ => format!("WAVE {}", enemies.number()),
+    };
+}
+
+fn spawn_text(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let style = |font_size| TextStyle {
+        font: asset_server.load(style::FONT),
+        font_size,
+        color: style::SCORE_COLOR,
+    };
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_sections([
+                    TextSection::from_style(style(40.0)),
+                    TextSection::new("\n\npress shoot to skip", style(16.0)),
+                ])
+                .with_text_alignment(TextAlignment::Center)
+                .with_style(Style {
+                    display: Display::None,
+                    ..default()
+                }),
+                IntroText,
+            ));
+        });
+}
+
+fn hide_text(mut query: Query<&mut Style, With<IntroText>>) {
+    let Ok(mut style) = query.get_single_mut() else {
+        return;
+    };
+    style.display = Display::None;
+}